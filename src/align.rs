@@ -0,0 +1,165 @@
+/// The largest number of element pairs that are aligned along their longest common subsequence.
+/// Beyond it the quadratic table gets too expensive, and elements are aligned by position.
+pub(crate) const MAX_ALIGNED_PAIRS: usize = 1 << 20;
+
+/// A step in the alignment of two sequences.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub(crate) enum Edit {
+    /// The element at `.0` in lhs and at `.1` in rhs are equal.
+    Equal(usize, usize),
+    /// The element at this index in lhs has no counterpart in rhs.
+    Delete(usize),
+    /// The element at this index in rhs has no counterpart in lhs.
+    Insert(usize),
+}
+
+/// Align two sequences of length `lhs_len` and `rhs_len` along their longest common
+/// subsequence, where `eq(i, j)` tells whether the element at `i` in lhs equals the element at `j`
+/// in rhs.
+///
+/// The edits are returned in order, and deletions come before insertions when both are possible.
+/// If more than [`MAX_ALIGNED_PAIRS`] pairs of elements are left after trimming the common prefix
+/// and suffix, the elements in between are aligned by position instead.
+pub(crate) fn align<F>(lhs_len: usize, rhs_len: usize, mut eq: F) -> Vec<Edit>
+where
+    F: FnMut(usize, usize) -> bool,
+{
+    // Matching prefixes and suffixes are common and don't need the quadratic table.
+    let mut prefix = 0;
+    while prefix < lhs_len && prefix < rhs_len && eq(prefix, prefix) {
+        prefix += 1;
+    }
+    let mut suffix = 0;
+    while suffix < lhs_len - prefix
+        && suffix < rhs_len - prefix
+        && eq(lhs_len - suffix - 1, rhs_len - suffix - 1)
+    {
+        suffix += 1;
+    }
+
+    let n = lhs_len - prefix - suffix;
+    let m = rhs_len - prefix - suffix;
+    if n.saturating_mul(m) > MAX_ALIGNED_PAIRS {
+        return align_by_position(lhs_len, rhs_len, eq);
+    }
+
+    let mut equal = vec![false; n * m];
+    for i in 0..n {
+        for j in 0..m {
+            equal[i * m + j] = eq(prefix + i, prefix + j);
+        }
+    }
+
+    // `lengths[i][j]` is the length of the longest common subsequence of `lhs[i..]` and
+    // `rhs[j..]`.
+    let width = m + 1;
+    let mut lengths = vec![0usize; (n + 1) * width];
+    for i in (0..n).rev() {
+        for j in (0..m).rev() {
+            lengths[i * width + j] = if equal[i * m + j] {
+                lengths[(i + 1) * width + j + 1] + 1
+            } else {
+                lengths[(i + 1) * width + j].max(lengths[i * width + j + 1])
+            };
+        }
+    }
+
+    let mut edits = (0..prefix).map(|i| Edit::Equal(i, i)).collect::<Vec<_>>();
+    let (mut i, mut j) = (0, 0);
+    while i < n || j < m {
+        if i < n && j < m && equal[i * m + j] {
+            edits.push(Edit::Equal(prefix + i, prefix + j));
+            i += 1;
+            j += 1;
+        } else if j == m || (i < n && lengths[(i + 1) * width + j] >= lengths[i * width + j + 1]) {
+            edits.push(Edit::Delete(prefix + i));
+            i += 1;
+        } else {
+            edits.push(Edit::Insert(prefix + j));
+            j += 1;
+        }
+    }
+    edits.extend((0..suffix).map(|k| Edit::Equal(prefix + n + k, prefix + m + k)));
+    edits
+}
+
+/// Align two sequences element by element, as if they were compared by position.
+fn align_by_position<F>(lhs_len: usize, rhs_len: usize, mut eq: F) -> Vec<Edit>
+where
+    F: FnMut(usize, usize) -> bool,
+{
+    let mut edits = vec![];
+    for idx in 0..lhs_len.min(rhs_len) {
+        if eq(idx, idx) {
+            edits.push(Edit::Equal(idx, idx));
+        } else {
+            edits.extend([Edit::Delete(idx), Edit::Insert(idx)]);
+        }
+    }
+    edits.extend((rhs_len..lhs_len).map(Edit::Delete));
+    edits.extend((lhs_len..rhs_len).map(Edit::Insert));
+    edits
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn align_chars(lhs: &str, rhs: &str) -> String {
+        let lhs = lhs.chars().collect::<Vec<_>>();
+        let rhs = rhs.chars().collect::<Vec<_>>();
+        align(lhs.len(), rhs.len(), |i, j| lhs[i] == rhs[j])
+            .into_iter()
+            .map(|edit| match edit {
+                Edit::Equal(i, _) => format!(" {}", lhs[i]),
+                Edit::Delete(i) => format!("-{}", lhs[i]),
+                Edit::Insert(j) => format!("+{}", rhs[j]),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_align() {
+        assert_eq!(align_chars("", ""), "");
+        assert_eq!(align_chars("abc", "abc"), " a b c");
+        assert_eq!(align_chars("abc", ""), "-a-b-c");
+        assert_eq!(align_chars("", "abc"), "+a+b+c");
+        assert_eq!(align_chars("abcd", "abxcd"), " a b+x c d");
+        assert_eq!(align_chars("abxcd", "abcd"), " a b-x c d");
+        assert_eq!(align_chars("abcd", "axcd"), " a-b+x c d");
+        assert_eq!(align_chars("xabc", "abcy"), "-x a b c+y");
+        assert_eq!(align_chars("aaa", "aa"), " a a-a");
+    }
+
+    #[test]
+    fn test_common_prefix_and_suffix_are_trimmed() {
+        let mut calls = 0;
+        align(20, 20, |i, j| {
+            calls += 1;
+            i == j && i != 10
+        });
+        // 11 for the prefix, 10 for the suffix and 1 for the single element in between.
+        assert_eq!(calls, 22);
+    }
+
+    #[test]
+    fn test_large_sequences_are_aligned_by_position() {
+        // Every element of rhs is shifted by one, which the longest common subsequence would
+        // find, but the sequences are too long for it.
+        let len = 1 << 11;
+        assert!(len * len > MAX_ALIGNED_PAIRS);
+        let edits = align(len, len, |i, j| i == j + 1);
+        assert_eq!(edits.len(), 2 * len);
+        assert_eq!(edits[..2], [Edit::Delete(0), Edit::Insert(0)]);
+        assert!(!edits.iter().any(|edit| matches!(edit, Edit::Equal(..))));
+
+        let edits = align(len + 1, len, |_, _| false);
+        assert_eq!(edits[2 * len], Edit::Delete(len));
+
+        let edits = align(len, len, |i, j| i == j && i % 2 == 1);
+        assert_eq!(
+            edits[..3],
+            [Edit::Delete(0), Edit::Insert(0), Edit::Equal(1, 1)]
+        );
+    }
+}
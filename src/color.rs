@@ -0,0 +1,146 @@
+use crate::core_ext::Indent;
+use std::{fmt, io::IsTerminal, ops::Range};
+
+const RESET: &str = "\x1b[0m";
+const PATH: &str = "\x1b[1;36m";
+const EXPECTED: &str = "\x1b[32m";
+const ACTUAL: &str = "\x1b[31m";
+const CHANGED: &str = "\x1b[1;4m";
+const UNCHANGED: &str = "\x1b[22;24m";
+
+/// Returns `true` if failure messages should be colored.
+///
+/// Colors require the `color` feature, and are disabled when stderr is not a terminal or when the
+/// `NO_COLOR` environment variable is set to a non-empty value.
+pub(crate) fn enabled() -> bool {
+    cfg!(feature = "color")
+        && std::env::var_os("NO_COLOR").unwrap_or_default().is_empty()
+        && std::io::stderr().is_terminal()
+}
+
+/// Which side of a comparison a value comes from.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub(crate) enum Side {
+    /// The expected value, or rhs.
+    Expected,
+    /// The actual value, or lhs.
+    Actual,
+}
+
+/// Styles the parts of a failure message, or leaves them as they are if `color` is `false`.
+#[derive(Debug, Copy, Clone)]
+pub(crate) struct Painter {
+    pub(crate) color: bool,
+}
+
+impl Painter {
+    pub(crate) fn path(&self, path: impl fmt::Display) -> String {
+        if self.color {
+            format!("{}{}{}", PATH, path, RESET)
+        } else {
+            path.to_string()
+        }
+    }
+
+    /// Prefix a line of a unified diff with `-` or `+` depending on `side`, or with a space for
+    /// context lines.
+    pub(crate) fn diff_line(&self, side: Option<Side>, line: &str) -> String {
+        match (side, self.color) {
+            (None, _) => format!(" {}", line),
+            (Some(Side::Actual), false) => format!("-{}", line),
+            (Some(Side::Expected), false) => format!("+{}", line),
+            (Some(Side::Actual), true) => format!("{}-{}{}", ACTUAL, line, RESET),
+            (Some(Side::Expected), true) => format!("{}+{}{}", EXPECTED, line, RESET),
+        }
+    }
+
+    /// Indent `value` by `level` and color it by `side`, emphasizing the bytes in `changed`.
+    pub(crate) fn value(
+        &self,
+        value: &str,
+        side: Side,
+        changed: Option<Range<usize>>,
+        level: u32,
+    ) -> String {
+        if !self.color {
+            return value.indent(level);
+        }
+
+        let color = match side {
+            Side::Expected => EXPECTED,
+            Side::Actual => ACTUAL,
+        };
+        let changed = changed.unwrap_or(0..0);
+        let indent = " ".repeat(level as usize);
+
+        // Every line is styled on its own so that the output still reads well when it is
+        // interleaved with other output or displayed one line at a time.
+        let mut lines = vec![];
+        let mut start = 0;
+        for line in value.split('\n') {
+            let end = start + line.len();
+            let from = changed.start.clamp(start, end);
+            let to = changed.end.clamp(from, end);
+            let mut styled = format!("{}{}{}", indent, color, &value[start..from]);
+            if from < to {
+                styled.push_str(&format!("{}{}{}", CHANGED, &value[from..to], UNCHANGED));
+            }
+            styled.push_str(&value[to..end]);
+            styled.push_str(RESET);
+            lines.push(styled);
+            start = end + 1;
+        }
+        lines.join("\n")
+    }
+}
+
+/// Returns the byte ranges of `lhs` and `rhs` that remain once their common prefix and suffix are
+/// removed.
+pub(crate) fn changed(lhs: &str, rhs: &str) -> (Range<usize>, Range<usize>) {
+    let prefix = lhs
+        .char_indices()
+        .zip(rhs.chars())
+        .find(|((_, l), r)| l != r)
+        .map_or(lhs.len().min(rhs.len()), |((idx, _), _)| idx);
+    let suffix = lhs[prefix..]
+        .chars()
+        .rev()
+        .zip(rhs[prefix..].chars().rev())
+        .take_while(|(l, r)| l == r)
+        .map(|(l, _)| l.len_utf8())
+        .sum::<usize>();
+    (prefix..lhs.len() - suffix, prefix..rhs.len() - suffix)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_changed() {
+        assert_eq!(changed("abc", "abc"), (3..3, 3..3));
+        assert_eq!(changed("\"bob\"", "\"rob\""), (1..2, 1..2));
+        assert_eq!(changed("abc", "abxc"), (2..2, 2..3));
+        assert_eq!(changed("aa", "aaa"), (2..2, 2..3));
+        assert_eq!(changed("1", "\"x\""), (0..1, 0..3));
+        assert_eq!(changed("\"å\"", "\"ä\""), (1..3, 1..3));
+    }
+
+    #[test]
+    fn test_value() {
+        let plain = Painter { color: false };
+        assert_eq!(plain.value("1\n2", Side::Actual, Some(0..1), 2), "  1\n  2");
+        assert_eq!(plain.path(".a"), ".a");
+
+        let painter = Painter { color: true };
+        assert_eq!(
+            painter.value("\"bob\"", Side::Expected, Some(1..2), 2),
+            "  \x1b[32m\"\x1b[1;4mb\x1b[22;24mob\"\x1b[0m"
+        );
+        assert_eq!(
+            painter.value("[\n  1\n]", Side::Actual, Some(4..5), 0),
+            "\x1b[31m[\x1b[0m\n\x1b[31m  \x1b[1;4m1\x1b[22;24m\x1b[0m\n\x1b[31m]\x1b[0m"
+        );
+        assert_eq!(painter.path(".a"), "\x1b[1;36m.a\x1b[0m");
+    }
+}
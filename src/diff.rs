@@ -1,41 +1,234 @@
-use crate::core_ext::{Indent, Indexes};
+use crate::align::{align, Edit};
+use crate::color::{self, Painter, Side};
+use crate::core_ext::Indexes;
+use crate::matcher::{self, Matcher};
+use crate::matching::max_matching;
+use crate::patch::{array_index, parse_pointer};
+use crate::pattern::{parse_path, write_field, ParsePathError};
 use crate::{ArraySortingMode, CompareMode, Config, FloatCompareMode, NumericMode};
 use float_cmp::{ApproxEq, F64Margin, FloatMargin};
-use serde_json::Value;
-use std::{collections::HashSet, fmt};
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+use serde_json::{Map, Value};
+use std::{collections::BTreeSet, fmt, mem, str::FromStr};
 
 pub(crate) fn diff<'a>(
     lhs: &'a Value,
     rhs: &'a Value,
     config: &'a Config,
 ) -> Vec<DifferenceRef<'a>> {
-    let mut acc = vec![];
-    diff_with(lhs, rhs, config, PathRef::Root, &mut acc);
-    acc
+    diff_counted(lhs, rhs, config).0
+}
+
+/// Like [`diff`], but also returns the number of differences that were left out because of
+/// [`Config::max_differences`].
+pub(crate) fn diff_counted<'a>(
+    lhs: &'a Value,
+    rhs: &'a Value,
+    config: &'a Config,
+) -> (Vec<DifferenceRef<'a>>, usize) {
+    let mut acc = Differences::new(config.max_differences);
+    if !config.is_ignored(&PathRef::Root) {
+        diff_with(lhs, rhs, config, config, PathRef::Root, &mut acc);
+    }
+    (acc.found, acc.skipped)
+}
+
+/// Returns `true` if `lhs` and `rhs` have no differences, stopping at the first one.
+pub(crate) fn matches(lhs: &Value, rhs: &Value, config: &Config) -> bool {
+    let mut acc = Differences::first();
+    if !config.is_ignored(&PathRef::Root) {
+        diff_with(lhs, rhs, config, config, PathRef::Root, &mut acc);
+    }
+    acc.is_empty()
+}
+
+/// The differences found so far. Once `limit` differences are found the rest are only counted,
+/// or the walk stops at the next one if `stop` is set.
+#[derive(Debug)]
+struct Differences<'a> {
+    found: Vec<DifferenceRef<'a>>,
+    limit: Option<usize>,
+    skipped: usize,
+    stop: bool,
+}
+
+impl<'a> Differences<'a> {
+    fn new(limit: Option<usize>) -> Self {
+        Differences {
+            found: vec![],
+            limit,
+            skipped: 0,
+            stop: false,
+        }
+    }
+
+    /// Only look for whether there is a difference, without keeping it.
+    fn first() -> Self {
+        Differences {
+            stop: true,
+            ..Differences::new(Some(0))
+        }
+    }
+
+    fn push(&mut self, difference: DifferenceRef<'a>) {
+        if self.is_full() {
+            self.skipped += 1;
+        } else {
+            self.found.push(difference);
+        }
+    }
+
+    /// Returns `true` if new differences are only counted.
+    fn is_full(&self) -> bool {
+        self.limit.is_some_and(|limit| self.found.len() >= limit)
+    }
+
+    /// Returns `true` if the walk can stop.
+    fn is_done(&self) -> bool {
+        self.stop && self.skipped > 0
+    }
+
+    fn is_empty(&self) -> bool {
+        self.found.is_empty() && self.skipped == 0
+    }
 }
 
 fn diff_with<'a>(
     lhs: &'a Value,
     rhs: &'a Value,
+    root: &'a Config,
     config: &'a Config,
     path: PathRef<'a>,
-    acc: &mut Vec<DifferenceRef<'a>>,
+    acc: &mut Differences<'a>,
 ) {
+    if acc.is_done() {
+        return;
+    }
+
+    let config = config_at(root, config, &path);
     let mut folder = DiffFolder {
         rhs,
         path,
         acc,
+        root,
         config,
     };
 
     fold_json(lhs, &mut folder);
 }
 
+/// Returns `true` if `lhs` and `rhs`, found at `path` below a value compared with `config`, have
+/// no differences, stopping at the first one.
+pub(crate) fn matches_at<'a>(
+    lhs: &'a Value,
+    rhs: &'a Value,
+    root: &'a Config,
+    config: &'a Config,
+    path: PathRef<'a>,
+) -> bool {
+    let mut acc = Differences::first();
+    diff_with(lhs, rhs, root, config, path, &mut acc);
+    acc.is_empty()
+}
+
+/// A pair of array elements, by their index in lhs and in rhs. Elements without a counterpart on
+/// the other side are paired with `None`.
+#[derive(Debug)]
+pub(crate) struct ElementPair<'a> {
+    pub(crate) lhs: Option<usize>,
+    pub(crate) rhs: Option<usize>,
+    /// The key of the pair in paths.
+    pub(crate) key: KeyRef<'a>,
+}
+
+/// Pairs up the elements of the arrays `lhs` and `rhs`, found at `path` and compared with
+/// `config`, the same way their differences are found.
+///
+/// Ordered arrays are paired in order. Otherwise the pairs are sorted by their index in lhs,
+/// followed by the elements that are only in rhs. Unmatched rhs elements of arrays compared
+/// without considering their order are paired with their closest match.
+pub(crate) fn array_pairs<'a>(
+    lhs: &'a Value,
+    rhs: &'a Value,
+    root: &'a Config,
+    config: &'a Config,
+    path: PathRef<'a>,
+) -> Vec<ElementPair<'a>> {
+    let (lhs, rhs_items) = (lhs.as_array().unwrap(), rhs.as_array().unwrap());
+    let mut acc = Differences::new(None);
+    let folder = DiffFolder {
+        rhs,
+        path,
+        acc: &mut acc,
+        root,
+        config,
+    };
+
+    let mut pairs = match &config.array_sorting_mode {
+        ArraySortingMode::Consider => {
+            return (0..lhs.len().max(rhs_items.len()))
+                .map(|idx| ElementPair {
+                    lhs: Some(idx).filter(|&idx| idx < lhs.len()),
+                    rhs: Some(idx).filter(|&idx| idx < rhs_items.len()),
+                    key: KeyRef::Idx(idx),
+                })
+                .collect();
+        }
+        ArraySortingMode::Align => return folder.aligned_pairs(lhs, rhs_items),
+        ArraySortingMode::Key(keys) => folder.keyed_pairs(lhs, rhs_items, keys),
+        ArraySortingMode::Ignore => {
+            let (pairs, mut lhs_unmatched, rhs_unmatched) =
+                folder.unordered_matching(lhs, rhs_items);
+            let mut pairs = pairs
+                .into_iter()
+                .map(|(lhs_idx, rhs_idx)| ElementPair {
+                    lhs: Some(lhs_idx),
+                    rhs: Some(rhs_idx),
+                    key: KeyRef::Idx(lhs_idx),
+                })
+                .collect::<Vec<_>>();
+            for rhs_idx in rhs_unmatched {
+                pairs.push(ElementPair {
+                    lhs: folder.take_closest(lhs, &rhs_items[rhs_idx], &mut lhs_unmatched),
+                    rhs: Some(rhs_idx),
+                    key: KeyRef::Idx(rhs_idx),
+                });
+            }
+            pairs.extend(lhs_unmatched.into_iter().map(|lhs_idx| ElementPair {
+                lhs: Some(lhs_idx),
+                rhs: None,
+                key: KeyRef::Idx(lhs_idx),
+            }));
+            pairs
+        }
+    };
+    pairs.sort_by_key(|pair| (pair.lhs.is_none(), pair.lhs, pair.rhs));
+    pairs
+}
+
+/// Returns the config for the value at `path`, below a value compared with `config`.
+pub(crate) fn config_at<'a>(
+    root: &'a Config,
+    config: &'a Config,
+    path: &PathRef<'_>,
+) -> &'a Config {
+    // A path config applies to the whole subtree below the path it was registered for, so it is
+    // looked up from the root for every value. Path configs can have path configs of their own.
+    let Some(mut config) = root.config_at(path) else {
+        return config;
+    };
+    while let Some(inner) = config.config_at(path) {
+        config = inner;
+    }
+    config
+}
+
 #[derive(Debug)]
 struct DiffFolder<'a, 'b> {
     rhs: &'a Value,
     path: PathRef<'a>,
-    acc: &'b mut Vec<DifferenceRef<'a>>,
+    acc: &'b mut Differences<'a>,
+    root: &'a Config,
     config: &'a Config,
 }
 
@@ -47,7 +240,9 @@ macro_rules! direct_compare {
                     lhs: Some(lhs),
                     rhs: Some(&self.rhs),
                     path: self.path.clone(),
-                    config: self.config.clone(),
+                    lengths: None,
+                    config: self.config,
+                    unmatched: None,
                 });
             }
         }
@@ -72,7 +267,36 @@ impl<'a> DiffFolder<'a, '_> {
                 lhs: Some(lhs),
                 rhs: Some(self.rhs),
                 path: self.path.clone(),
-                config: self.config.clone(),
+                lengths: None,
+                config: self.config,
+                unmatched: None,
+            });
+        }
+    }
+
+    /// Compare `lhs` with the string that an escaped matcher in rhs stands for.
+    fn on_literal(&mut self, lhs: &'a Value, literal: &str) {
+        if lhs.as_str() != Some(literal) {
+            self.acc.push(DifferenceRef {
+                lhs: Some(lhs),
+                rhs: Some(self.rhs),
+                path: self.path.clone(),
+                lengths: None,
+                config: self.config,
+                unmatched: None,
+            });
+        }
+    }
+
+    fn on_matcher(&mut self, lhs: &'a Value, matcher: Matcher) {
+        if !matcher.matches(lhs) {
+            self.acc.push(DifferenceRef {
+                lhs: Some(lhs),
+                rhs: Some(self.rhs),
+                path: self.path.clone(),
+                lengths: None,
+                config: self.config,
+                unmatched: None,
             });
         }
     }
@@ -99,57 +323,392 @@ impl<'a> DiffFolder<'a, '_> {
     }
     fn on_array_contains(&mut self, lhs: &'a Value) {
         if let Some(rhs) = self.rhs.as_array() {
-            let lhs_array = lhs.as_array().unwrap();
-
-            let lhs_len = lhs_array.len();
-            let rhs_len = rhs.len();
+            let lhs = lhs.as_array().unwrap();
+            let (_, mut lhs_unmatched, rhs_unmatched) = self.unordered_matching(lhs, rhs);
 
-            if self.config.compare_mode == CompareMode::Strict && lhs_len != rhs_len {
+            // Each unmatched rhs item is reported together with the closest lhs item that is
+            // still unpaired, which is then no longer reported as extra.
+            for &rhs_idx in &rhs_unmatched {
+                if self.acc.is_done() {
+                    break;
+                }
+                let path = self.path.append(KeyRef::Idx(rhs_idx));
+                if self.is_ignored(&path) {
+                    continue;
+                }
+                // The closest item is only looked for if the difference is kept. Otherwise any
+                // unpaired item will do, so that the extra items are still counted right.
+                let closest = if self.acc.is_full() {
+                    lhs_unmatched.pop()
+                } else {
+                    self.take_closest(lhs, &rhs[rhs_idx], &mut lhs_unmatched)
+                };
                 self.acc.push(DifferenceRef {
-                    lhs: Some(lhs),
-                    rhs: Some(self.rhs),
-                    path: self.path.clone(),
-                    config: self.config.clone(),
+                    lhs: closest.map(|lhs_idx| &lhs[lhs_idx]),
+                    rhs: Some(&rhs[rhs_idx]),
+                    path,
+                    lengths: None,
+                    config: self.config,
+                    unmatched: Some(Unmatched::Rhs(
+                        closest.map(|lhs_idx| self.path.append(KeyRef::Idx(lhs_idx))),
+                    )),
                 });
-                return;
             }
 
-            for rhs_item in rhs.iter() {
-                // For each rhs item (expected) count the number of times it matches with the rhs
-                // (expected) array.
-                let rhs_item_count = rhs
-                    .iter()
-                    .filter(|i| diff(rhs_item, i, self.config).is_empty())
-                    .count();
-                // Make sure that lhs (actual) has at least as many items matching the rhs
-                // (expected) item.
-                let lhs_matching_items_count = lhs_array
-                    .iter()
-                    .filter(|lhs_item| diff(lhs_item, rhs_item, self.config).is_empty())
-                    .count();
-                if lhs_matching_items_count < rhs_item_count {
+            if self.config.compare_mode == CompareMode::Strict {
+                for &lhs_idx in &lhs_unmatched {
+                    if self.acc.is_done() {
+                        break;
+                    }
+                    let path = self.path.append(KeyRef::Idx(lhs_idx));
+                    if self.is_ignored(&path) {
+                        continue;
+                    }
                     self.acc.push(DifferenceRef {
-                        lhs: Some(lhs),
-                        rhs: Some(self.rhs),
-                        path: self.path.clone(),
-                        config: self.config.clone(),
+                        lhs: Some(&lhs[lhs_idx]),
+                        rhs: None,
+                        path,
+                        lengths: None,
+                        config: self.config,
+                        unmatched: Some(Unmatched::Lhs),
                     });
+                }
+            }
+        } else {
+            self.acc.push(DifferenceRef {
+                lhs: Some(lhs),
+                rhs: Some(self.rhs),
+                path: self.path.clone(),
+                lengths: None,
+                config: self.config,
+                unmatched: None,
+            });
+        }
+    }
+
+    /// Pair rhs items (expected) with lhs items (actual) that match them, so that as many as
+    /// possible are paired, even when an actual item matches several expected ones. Returns the
+    /// pairs as `(lhs_idx, rhs_idx)`, and the unpaired lhs and rhs items.
+    fn unordered_matching(
+        &self,
+        lhs: &'a [Value],
+        rhs: &'a [Value],
+    ) -> (Vec<(usize, usize)>, Vec<usize>, Vec<usize>) {
+        // Compare every pair once, then find a maximum matching.
+        let edges = rhs
+            .iter()
+            .map(|rhs_item| {
+                (0..lhs.len())
+                    .filter(|&lhs_idx| {
+                        self.matches_at(&lhs[lhs_idx], rhs_item, KeyRef::Idx(lhs_idx))
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+        let matching = max_matching(&edges, lhs.len());
+
+        let mut lhs_matched = vec![false; lhs.len()];
+        let mut pairs = vec![];
+        let mut rhs_unmatched = vec![];
+        for (rhs_idx, lhs_idx) in matching.into_iter().enumerate() {
+            match lhs_idx {
+                Some(lhs_idx) => {
+                    lhs_matched[lhs_idx] = true;
+                    pairs.push((lhs_idx, rhs_idx));
+                }
+                None => rhs_unmatched.push(rhs_idx),
+            }
+        }
+        let lhs_unmatched = (0..lhs.len())
+            .filter(|&lhs_idx| !lhs_matched[lhs_idx])
+            .collect::<Vec<_>>();
+        (pairs, lhs_unmatched, rhs_unmatched)
+    }
+
+    /// Removes the lhs item in `lhs_unmatched` that is closest to `rhs_item` and returns it.
+    fn take_closest(
+        &self,
+        lhs: &'a [Value],
+        rhs_item: &'a Value,
+        lhs_unmatched: &mut Vec<usize>,
+    ) -> Option<usize> {
+        let closest = closest(lhs_unmatched, |lhs_idx| {
+            self.count_differences(&lhs[lhs_idx], rhs_item, KeyRef::Idx(lhs_idx))
+        });
+        lhs_unmatched.retain(|&lhs_idx| Some(lhs_idx) != closest);
+        closest
+    }
+
+    /// Returns the number of differences between `lhs` and `rhs`, found at `key` below the
+    /// current path.
+    fn count_differences(&self, lhs: &'a Value, rhs: &'a Value, key: KeyRef<'a>) -> usize {
+        let mut acc = Differences::new(None);
+        diff_with(
+            lhs,
+            rhs,
+            self.root,
+            self.config,
+            self.path.append(key),
+            &mut acc,
+        );
+        acc.found.len()
+    }
+
+    fn is_ignored(&self, path: &PathRef<'a>) -> bool {
+        self.config.is_ignored(path) || self.root.is_ignored(path)
+    }
+
+    /// Returns `true` if `lhs` and `rhs`, found at `key` below the current path, have no
+    /// differences.
+    fn matches_at(&self, lhs: &'a Value, rhs: &'a Value, key: KeyRef<'a>) -> bool {
+        matches_at(lhs, rhs, self.root, self.config, self.path.append(key))
+    }
+
+    fn on_array_keyed(&mut self, lhs: &'a Value, keys: &'a [String]) {
+        if let Some(rhs) = self.rhs.as_array() {
+            let lhs = lhs.as_array().unwrap();
+            for pair in self.keyed_pairs(lhs, rhs, keys) {
+                if self.acc.is_done() {
+                    break;
+                }
+                self.on_element_pair(lhs, rhs, &pair);
+            }
+        } else {
+            self.acc.push(DifferenceRef {
+                lhs: Some(lhs),
+                rhs: Some(self.rhs),
+                path: self.path.clone(),
+                lengths: None,
+                config: self.config,
+                unmatched: None,
+            });
+        }
+    }
+
+    /// Pair each rhs element (expected) that has all of the keys with the first unused lhs element
+    /// (actual) whose key values match. Elements without the keys are paired by their position
+    /// among the other elements without the keys.
+    ///
+    /// The pairs are returned in the order of rhs, followed by the unpaired lhs elements.
+    fn keyed_pairs(
+        &self,
+        lhs: &'a [Value],
+        rhs: &'a [Value],
+        keys: &'a [String],
+    ) -> Vec<ElementPair<'a>> {
+        let key = |idx, element| {
+            if has_keys(element, keys) {
+                KeyRef::keyed(idx, element, keys)
+            } else {
+                KeyRef::Idx(idx)
+            }
+        };
+
+        let mut lhs_keyless = lhs
+            .iter()
+            .enumerate()
+            .filter(|(_, lhs)| !has_keys(lhs, keys))
+            .map(|(idx, _)| idx);
+        let mut lhs_matched = vec![false; lhs.len()];
+        let mut pairs = vec![];
+        for (rhs_idx, rhs_item) in rhs.iter().enumerate() {
+            let lhs_idx = if has_keys(rhs_item, keys) {
+                (0..lhs.len()).find(|&lhs_idx| {
+                    !lhs_matched[lhs_idx]
+                        && has_keys(&lhs[lhs_idx], keys)
+                        && self.keys_match(lhs_idx, &lhs[lhs_idx], rhs_item, keys)
+                })
+            } else {
+                lhs_keyless.next()
+            };
+
+            pairs.push(match lhs_idx {
+                Some(lhs_idx) => {
+                    lhs_matched[lhs_idx] = true;
+                    ElementPair {
+                        lhs: Some(lhs_idx),
+                        rhs: Some(rhs_idx),
+                        key: key(lhs_idx, &lhs[lhs_idx]),
+                    }
+                }
+                None => ElementPair {
+                    lhs: None,
+                    rhs: Some(rhs_idx),
+                    key: key(rhs_idx, rhs_item),
+                },
+            });
+        }
+        pairs.extend(
+            lhs.iter()
+                .enumerate()
+                .filter(|&(lhs_idx, _)| !lhs_matched[lhs_idx])
+                .map(|(lhs_idx, lhs_item)| ElementPair {
+                    lhs: Some(lhs_idx),
+                    rhs: None,
+                    key: key(lhs_idx, lhs_item),
+                }),
+        );
+        pairs
+    }
+
+    /// Returns what is recorded as rhs for a value missing from lhs: the value itself, or the
+    /// object or array that contains it in [`CompareMode::Inclusive`]. See
+    /// [`Difference::expected`].
+    fn missing_rhs(&self, rhs: &'a Value) -> &'a Value {
+        match self.config.compare_mode {
+            CompareMode::Inclusive => self.rhs,
+            CompareMode::Strict => rhs,
+        }
+    }
+
+    /// Compare a pair of array elements. Elements without a counterpart are reported as missing
+    /// from actual, or as extra in actual in [`CompareMode::Strict`].
+    fn on_element_pair(&mut self, lhs: &'a [Value], rhs: &'a [Value], pair: &ElementPair<'a>) {
+        let path = self.path.append(pair.key);
+        if self.is_ignored(&path) {
+            return;
+        }
+        match (pair.lhs, pair.rhs) {
+            (Some(lhs_idx), Some(rhs_idx)) => diff_with(
+                &lhs[lhs_idx],
+                &rhs[rhs_idx],
+                self.root,
+                self.config,
+                path,
+                self.acc,
+            ),
+            (None, Some(rhs_idx)) => self.acc.push(DifferenceRef {
+                lhs: None,
+                rhs: Some(self.missing_rhs(&rhs[rhs_idx])),
+                path,
+                lengths: None,
+                config: self.config,
+                unmatched: None,
+            }),
+            (Some(lhs_idx), None) if self.config.compare_mode == CompareMode::Strict => {
+                self.acc.push(DifferenceRef {
+                    lhs: Some(&lhs[lhs_idx]),
+                    rhs: None,
+                    path,
+                    lengths: None,
+                    config: self.config,
+                    unmatched: None,
+                })
+            }
+            _ => {}
+        }
+    }
+
+    /// Returns `true` if the values of `keys` in the actual element at `lhs_idx` match the ones
+    /// in `rhs`, compared like any other value so that matchers and numeric modes apply.
+    fn keys_match(
+        &self,
+        lhs_idx: usize,
+        lhs: &'a Value,
+        rhs: &'a Value,
+        keys: &'a [String],
+    ) -> bool {
+        let path = self.path.append(KeyRef::Idx(lhs_idx));
+        keys.iter().all(|key| {
+            let mut acc = Differences::first();
+            diff_with(
+                &lhs[key.as_str()],
+                &rhs[key.as_str()],
+                self.root,
+                self.config,
+                path.append(KeyRef::Field(key)),
+                &mut acc,
+            );
+            acc.is_empty()
+        })
+    }
+
+    fn on_array_aligned(&mut self, lhs: &'a Value) {
+        if let Some(rhs) = self.rhs.as_array() {
+            let lhs = lhs.as_array().unwrap();
+            for pair in self.aligned_pairs(lhs, rhs) {
+                if self.acc.is_done() {
                     break;
                 }
+                self.on_element_pair(lhs, rhs, &pair);
             }
         } else {
             self.acc.push(DifferenceRef {
                 lhs: Some(lhs),
                 rhs: Some(self.rhs),
                 path: self.path.clone(),
-                config: self.config.clone(),
+                lengths: None,
+                config: self.config,
+                unmatched: None,
             });
         }
     }
 
+    /// Pair the elements of two arrays along their longest common subsequence.
+    ///
+    /// Runs of deleted and inserted elements between two equal elements are paired up as changed
+    /// elements. What's left over is returned without a counterpart, deleted elements first.
+    fn aligned_pairs(&self, lhs: &'a [Value], rhs: &'a [Value]) -> Vec<ElementPair<'a>> {
+        let edits = align(lhs.len(), rhs.len(), |lhs_idx, rhs_idx| {
+            self.matches_at(&lhs[lhs_idx], &rhs[rhs_idx], KeyRef::Idx(lhs_idx))
+        });
+
+        let mut pairs = vec![];
+        let mut deleted = vec![];
+        let mut inserted = vec![];
+        for edit in edits.into_iter().map(Some).chain([None]) {
+            match edit {
+                Some(Edit::Delete(idx)) => deleted.push(idx),
+                Some(Edit::Insert(idx)) => inserted.push(idx),
+                Some(Edit::Equal(..)) | None => {
+                    pairs.extend(deleted.iter().zip(&inserted).map(|(&lhs_idx, &rhs_idx)| {
+                        ElementPair {
+                            lhs: Some(lhs_idx),
+                            rhs: Some(rhs_idx),
+                            key: KeyRef::Idx(lhs_idx),
+                        }
+                    }));
+                    pairs.extend(
+                        deleted
+                            .iter()
+                            .skip(inserted.len())
+                            .map(|&lhs_idx| ElementPair {
+                                lhs: Some(lhs_idx),
+                                rhs: None,
+                                key: KeyRef::Idx(lhs_idx),
+                            }),
+                    );
+                    pairs.extend(
+                        inserted
+                            .iter()
+                            .skip(deleted.len())
+                            .map(|&rhs_idx| ElementPair {
+                                lhs: None,
+                                rhs: Some(rhs_idx),
+                                key: KeyRef::Idx(rhs_idx),
+                            }),
+                    );
+                    deleted.clear();
+                    inserted.clear();
+                    if let Some(Edit::Equal(lhs_idx, rhs_idx)) = edit {
+                        pairs.push(ElementPair {
+                            lhs: Some(lhs_idx),
+                            rhs: Some(rhs_idx),
+                            key: KeyRef::Idx(lhs_idx),
+                        });
+                    }
+                }
+            }
+        }
+        pairs
+    }
+
     fn on_array(&mut self, lhs: &'a Value) {
-        if self.config.array_sorting_mode == ArraySortingMode::Ignore {
-            return self.on_array_contains(lhs);
+        match &self.config.array_sorting_mode {
+            ArraySortingMode::Consider => {}
+            ArraySortingMode::Ignore => return self.on_array_contains(lhs),
+            ArraySortingMode::Key(keys) => return self.on_array_keyed(lhs, keys),
+            ArraySortingMode::Align => return self.on_array_aligned(lhs),
         }
 
         if let Some(rhs) = self.rhs.as_array() {
@@ -157,40 +716,58 @@ impl<'a> DiffFolder<'a, '_> {
 
             match self.config.compare_mode {
                 CompareMode::Inclusive => {
+                    let rhs_len = rhs.len();
                     for (idx, rhs) in rhs.iter().enumerate() {
+                        if self.acc.is_done() {
+                            break;
+                        }
                         let path = self.path.append(KeyRef::Idx(idx));
+                        if self.is_ignored(&path) {
+                            continue;
+                        }
 
                         if let Some(lhs) = lhs.get(idx) {
-                            diff_with(lhs, rhs, self.config, path, self.acc)
+                            diff_with(lhs, rhs, self.root, self.config, path, self.acc)
                         } else {
                             self.acc.push(DifferenceRef {
                                 lhs: None,
                                 rhs: Some(self.rhs),
                                 path,
-                                config: self.config.clone(),
+                                lengths: Some((lhs.len(), rhs_len)),
+                                config: self.config,
+                                unmatched: None,
                             });
                         }
                     }
                 }
                 CompareMode::Strict => {
+                    let lengths = Some((lhs.len(), rhs.len()));
                     let all_keys = rhs
                         .indexes()
                         .into_iter()
                         .chain(lhs.indexes())
-                        .collect::<HashSet<_>>();
+                        .collect::<BTreeSet<_>>();
                     for key in all_keys {
+                        if self.acc.is_done() {
+                            break;
+                        }
                         let path = self.path.append(KeyRef::Idx(key));
+                        if self.is_ignored(&path) {
+                            continue;
+                        }
 
                         match (lhs.get(key), rhs.get(key)) {
                             (Some(lhs), Some(rhs)) => {
-                                diff_with(lhs, rhs, self.config, path, self.acc);
+                                diff_with(lhs, rhs, self.root, self.config, path, self.acc);
                             }
                             (None, Some(rhs)) => {
                                 self.acc.push(DifferenceRef {
                                     lhs: None,
                                     rhs: Some(rhs),
                                     path,
-                                    config: self.config.clone(),
+                                    lengths,
+                                    config: self.config,
+                                    unmatched: None,
                                 });
                             }
                             (Some(lhs), None) => {
@@ -198,7 +775,9 @@ impl<'a> DiffFolder<'a, '_> {
                                     lhs: Some(lhs),
                                     rhs: None,
                                     path,
-                                    config: self.config.clone(),
+                                    lengths,
+                                    config: self.config,
+                                    unmatched: None,
                                 });
                             }
                             (None, None) => {
@@ -213,7 +792,9 @@ impl<'a> DiffFolder<'a, '_> {
                 lhs: Some(lhs),
                 rhs: Some(self.rhs),
                 path: self.path.clone(),
-                config: self.config.clone(),
+                lengths: None,
+                config: self.config,
+                unmatched: None,
             });
         }
     }
@@ -225,35 +806,54 @@ impl<'a> DiffFolder<'a, '_> {
             match self.config.compare_mode {
                 CompareMode::Inclusive => {
                     for (key, rhs) in rhs.iter() {
+                        if self.acc.is_done() {
+                            break;
+                        }
                         let path = self.path.append(KeyRef::Field(key));
+                        if self.is_ignored(&path) {
+                            continue;
+                        }
 
                         if let Some(lhs) = lhs.get(key) {
-                            diff_with(lhs, rhs, self.config, path, self.acc)
+                            diff_with(lhs, rhs, self.root, self.config, path, self.acc)
                         } else {
                             self.acc.push(DifferenceRef {
                                 lhs: None,
                                 rhs: Some(self.rhs),
                                 path,
-                                config: self.config.clone(),
+                                lengths: None,
+                                config: self.config,
+                                unmatched: None,
                             });
                         }
                     }
                 }
                 CompareMode::Strict => {
-                    let all_keys = rhs.keys().chain(lhs.keys()).collect::<HashSet<_>>();
+                    // Fields of rhs come first, in order, followed by the fields only in lhs.
+                    let all_keys = rhs
+                        .keys()
+                        .chain(lhs.keys().filter(|key| !rhs.contains_key(*key)));
                     for key in all_keys {
+                        if self.acc.is_done() {
+                            break;
+                        }
                         let path = self.path.append(KeyRef::Field(key));
+                        if self.is_ignored(&path) {
+                            continue;
+                        }
 
                         match (lhs.get(key), rhs.get(key)) {
                             (Some(lhs), Some(rhs)) => {
-                                diff_with(lhs, rhs, self.config, path, self.acc);
+                                diff_with(lhs, rhs, self.root, self.config, path, self.acc);
                             }
                             (None, Some(rhs)) => {
                                 self.acc.push(DifferenceRef {
                                     lhs: None,
                                     rhs: Some(rhs),
                                     path,
-                                    config: self.config.clone(),
+                                    lengths: None,
+                                    config: self.config,
+                                    unmatched: None,
                                 });
                             }
                             (Some(lhs), None) => {
@@ -261,7 +861,9 @@ impl<'a> DiffFolder<'a, '_> {
                                     lhs: Some(lhs),
                                     rhs: None,
                                     path,
-                                    config: self.config.clone(),
+                                    lengths: None,
+                                    config: self.config,
+                                    unmatched: None,
                                 });
                             }
                             (None, None) => {
@@ -276,23 +878,87 @@ impl<'a> DiffFolder<'a, '_> {
                 lhs: Some(lhs),
                 rhs: Some(self.rhs),
                 path: self.path.clone(),
-                config: self.config.clone(),
+                lengths: None,
+                config: self.config,
+                unmatched: None,
             });
         }
     }
 }
 
+/// Returns the candidate with the fewest differences.
+fn closest<F>(candidates: &[usize], count_differences: F) -> Option<usize>
+where
+    F: Fn(usize) -> usize,
+{
+    candidates
+        .iter()
+        .copied()
+        .min_by_key(|&candidate| count_differences(candidate))
+}
+
+/// Returns `true` if `element` is an object with all of the keys.
+fn has_keys(element: &Value, keys: &[String]) -> bool {
+    keys.iter().all(|key| element.get(key).is_some())
+}
+
 /// Represents a difference between two JSON values.
-#[derive(Debug, PartialEq, Clone)]
+///
+/// Differences can be serialized, for example to write comparison results to a report. The path
+/// is written as a JSON Pointer, and the configuration is reduced to its [`CompareMode`]:
+///
+/// ```
+/// use serde_json_assert::{try_assert_json_matches, CompareMode, Config};
+/// use serde_json::json;
+///
+/// let err = try_assert_json_matches(
+///     &json!({ "a": [1, 2] }),
+///     &json!({ "a": [1, 3] }),
+///     &Config::new(CompareMode::Strict),
+/// )
+/// .unwrap_err();
+///
+/// assert_eq!(
+///     serde_json::to_value(err.differences()).unwrap(),
+///     json!([{
+///         "path": "/a/1",
+///         "kind": "value_mismatch",
+///         "actual": 2,
+///         "expected": 3,
+///         "mode": "strict",
+///     }])
+/// );
+/// ```
+///
+/// Reports can be read back with `Deserialize`, but the result is not always equal to the
+/// difference that was written:
+///
+/// - The configuration is [`Config::new`] with the written [`CompareMode`].
+/// - Paths are read like [`Path::from_json_pointer`], so [`Key::Keyed`] elements and fields named
+///   like an array index come back as [`Key::Idx`].
+/// - For values missing from actual in [`CompareMode::Inclusive`],
+///   [`expected`](Difference::expected) is a new object or array that only holds the missing value,
+///   instead of the whole container.
+///
+/// Serializing a difference that was read back gives the same JSON again.
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+#[serde(into = "DifferenceRepr", from = "DifferenceRepr")]
 pub struct Difference {
     path: Path,
     lhs: Option<Value>,
     rhs: Option<Value>,
     config: Config,
+    unmatched: Option<Option<Path>>,
+    kind: DifferenceKind,
 }
 
 impl Difference {
     /// Returns the path to the difference.
+    ///
+    /// Array indexes in the path are positions in actual. Elements that are missing from actual
+    /// use their position in expected instead, and so do expected elements without a match when
+    /// arrays are compared without considering their order; the position in actual of their
+    /// closest match is returned by [`closest_match`](Difference::closest_match).
     pub fn path(&self) -> &Path {
         &self.path
     }
@@ -303,23 +969,229 @@ impl Difference {
     }
 
     /// Get the right-hand side, or "expected", value of the difference.
+    ///
+    /// For values missing from actual in [`CompareMode::Inclusive`] this is the object or array
+    /// that should contain them. Use [`expected_value`](Difference::expected_value) to get the
+    /// missing value itself.
     pub fn expected(&self) -> &Option<Value> {
         &self.rhs
     }
 
+    /// Returns the expected value at [`path`](Difference::path), if there is one.
+    ///
+    /// Unlike [`expected`](Difference::expected), this is the missing value itself for values
+    /// missing from actual in [`CompareMode::Inclusive`].
+    pub fn expected_value(&self) -> Option<&Value> {
+        let rhs = self.rhs.as_ref()?;
+        let is_missing_in_container = self.lhs.is_none()
+            && self.unmatched.is_none()
+            && self.config.compare_mode == CompareMode::Inclusive;
+        if !is_missing_in_container {
+            return Some(rhs);
+        }
+        match self.path.keys().last()? {
+            Key::Field(field) => rhs.get(field),
+            Key::Idx(idx) | Key::Keyed { idx, .. } => rhs.get(idx),
+        }
+    }
+
+    /// Returns the [`Matcher`] that rejected the actual value, if this difference was caused by a
+    /// placeholder in the expected value.
+    pub fn matcher(&self) -> Option<Matcher> {
+        match (&self.lhs, &self.rhs, &self.unmatched) {
+            (Some(_), Some(rhs), None) => Matcher::from_value(rhs),
+            _ => None,
+        }
+    }
+
+    /// Returns `true` if this difference is an array element without a match on the other side,
+    /// when comparing arrays without considering their order.
+    ///
+    /// In that case [`path`](Difference::path) points at the unmatched element, and the value on
+    /// the other side is the closest element that was left unpaired, if any. Its path is returned
+    /// by [`closest_match`](Difference::closest_match).
+    pub fn is_unmatched(&self) -> bool {
+        self.unmatched.is_some()
+    }
+
+    /// Returns the path to the element that came closest to matching an unmatched array element.
+    pub fn closest_match(&self) -> Option<&Path> {
+        self.unmatched.as_ref().and_then(Option::as_ref)
+    }
+
     /// Returns the configuration used to generate this difference.
     pub fn config(&self) -> &Config {
         &self.config
     }
+
+    /// Returns why the values differ.
+    pub fn kind(&self) -> DifferenceKind {
+        self.kind
+    }
+}
+
+/// Displays the same message as the assertion macros do for this difference. A precision, as in
+/// `{:.100}`, cuts off values longer than that many characters.
+impl fmt::Display for Difference {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let message = Message {
+            path: &self.path,
+            lhs: self.lhs.as_ref(),
+            rhs: self.expected_value(),
+            compare_mode: self.config.compare_mode,
+            unmatched: self.unmatched.as_ref().map(Option::as_ref),
+        };
+        fmt::Display::fmt(&message, f)
+    }
+}
+
+/// Why two JSON values differ.
+///
+/// ```
+/// use serde_json_assert::{try_assert_json_matches, CompareMode, Config, DifferenceKind};
+/// use serde_json::json;
+///
+/// let err = try_assert_json_matches(
+///     &json!({ "a": 1, "b": "2" }),
+///     &json!({ "a": 2, "b": 2, "c": 3 }),
+///     &Config::new(CompareMode::Inclusive),
+/// )
+/// .unwrap_err();
+///
+/// let kinds = err.differences().iter().map(|d| d.kind()).collect::<Vec<_>>();
+/// assert_eq!(
+///     kinds,
+///     [
+///         DifferenceKind::ValueMismatch,
+///         DifferenceKind::TypeMismatch,
+///         DifferenceKind::MissingInActual,
+///     ]
+/// );
+/// ```
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+#[non_exhaustive]
+pub enum DifferenceKind {
+    /// Both values have the same JSON type, but different values.
+    ValueMismatch,
+    /// The values have different JSON types, such as a string and a number.
+    TypeMismatch,
+    /// The value is in expected (rhs) but not in actual (lhs).
+    MissingInActual,
+    /// The value is in actual (lhs) but not in expected (rhs). Only reported by
+    /// [`CompareMode::Strict`].
+    UnexpectedInActual,
+    /// The arrays are compared element by element and one of them is longer than the other. The
+    /// difference points at an element past the end of the shorter array.
+    ArrayLengthMismatch,
+    /// An array element has no match on the other side, when arrays are compared without
+    /// considering their order. See [`Difference::is_unmatched`].
+    UnmatchedElement,
+    /// The actual value was rejected by a [`Matcher`] in expected. See [`Difference::matcher`].
+    MatcherMismatch,
 }
 
 impl<'a> From<DifferenceRef<'a>> for Difference {
     fn from(diff: DifferenceRef<'a>) -> Self {
+        let kind = diff.kind();
         Difference {
             path: Path::from(diff.path),
             lhs: diff.lhs.cloned(),
             rhs: diff.rhs.cloned(),
             config: diff.config.clone(),
+            kind,
+            unmatched: diff.unmatched.map(|unmatched| match unmatched {
+                Unmatched::Lhs => None,
+                Unmatched::Rhs(closest) => closest.map(Path::from),
+            }),
+        }
+    }
+}
+
+/// The serialized form of a [`Difference`].
+#[derive(Serialize, Deserialize)]
+#[serde(rename = "Difference")]
+struct DifferenceRepr {
+    path: Path,
+    kind: DifferenceKind,
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "present"
+    )]
+    actual: Option<Value>,
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "present"
+    )]
+    expected: Option<Value>,
+    #[serde(with = "CompareModeRepr")]
+    mode: CompareMode,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    unmatched: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    closest_match: Option<Path>,
+}
+
+/// Deserializes a member that is present, so that `null` isn't mistaken for a missing value.
+fn present<'de, D>(deserializer: D) -> Result<Option<Value>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    Value::deserialize(deserializer).map(Some)
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(remote = "CompareMode", rename_all = "lowercase")]
+enum CompareModeRepr {
+    Inclusive,
+    Strict,
+}
+
+impl From<Difference> for DifferenceRepr {
+    fn from(difference: Difference) -> Self {
+        let expected = difference.expected_value().cloned();
+        DifferenceRepr {
+            path: difference.path,
+            kind: difference.kind,
+            actual: difference.lhs,
+            expected,
+            mode: difference.config.compare_mode,
+            unmatched: difference.unmatched.is_some(),
+            closest_match: difference.unmatched.flatten(),
+        }
+    }
+}
+
+impl From<DifferenceRepr> for Difference {
+    fn from(repr: DifferenceRepr) -> Self {
+        let unmatched =
+            (repr.unmatched || repr.closest_match.is_some()).then_some(repr.closest_match);
+        // Values missing from actual in inclusive mode are kept in a container, like the one
+        // they are missing from, so that `expected_value` finds them.
+        let is_missing_in_container =
+            repr.actual.is_none() && unmatched.is_none() && repr.mode == CompareMode::Inclusive;
+        let rhs = match (repr.expected, repr.path.keys().last()) {
+            (Some(value), Some(Key::Field(field))) if is_missing_in_container => {
+                Some(Value::Object(Map::from_iter([(field.clone(), value)])))
+            }
+            (Some(value), Some(Key::Idx(idx) | Key::Keyed { idx, .. }))
+                if is_missing_in_container =>
+            {
+                let mut values = vec![Value::Null; *idx];
+                values.push(value);
+                Some(Value::Array(values))
+            }
+            (expected, _) => expected,
+        };
+        Difference {
+            path: repr.path,
+            kind: repr.kind,
+            lhs: repr.actual,
+            rhs,
+            config: Config::new(repr.mode),
+            unmatched,
         }
     }
 }
@@ -329,45 +1201,217 @@ pub(crate) struct DifferenceRef<'a> {
     path: PathRef<'a>,
     lhs: Option<&'a Value>,
     rhs: Option<&'a Value>,
-    config: Config,
+    /// The lengths of the lhs and rhs arrays, for elements missing from one of two arrays
+    /// compared by position.
+    lengths: Option<(usize, usize)>,
+    config: &'a Config,
+    unmatched: Option<Unmatched<'a>>,
+}
+
+impl<'a> DifferenceRef<'a> {
+    /// Returns the rhs value at the path, like [`Difference::expected_value`].
+    fn expected_value(&self) -> Option<&'a Value> {
+        let rhs = self.rhs?;
+        let is_missing_in_container = self.lhs.is_none()
+            && self.unmatched.is_none()
+            && self.config.compare_mode == CompareMode::Inclusive;
+        if !is_missing_in_container {
+            return Some(rhs);
+        }
+        match self.path.keys().last()? {
+            KeyRef::Field(field) => rhs.get(field),
+            KeyRef::Idx(idx) | KeyRef::Keyed { idx, .. } => rhs.get(idx),
+        }
+    }
+
+    fn kind(&self) -> DifferenceKind {
+        match (self.lhs, self.rhs) {
+            _ if self.unmatched.is_some() => DifferenceKind::UnmatchedElement,
+            (Some(_), Some(rhs)) if Matcher::from_value(rhs).is_some() => {
+                DifferenceKind::MatcherMismatch
+            }
+            (Some(lhs), Some(rhs)) if mem::discriminant(lhs) == mem::discriminant(rhs) => {
+                DifferenceKind::ValueMismatch
+            }
+            (Some(_), Some(_)) => DifferenceKind::TypeMismatch,
+            _ if self.lengths.is_some() => DifferenceKind::ArrayLengthMismatch,
+            (None, _) => DifferenceKind::MissingInActual,
+            (Some(_), None) => DifferenceKind::UnexpectedInActual,
+        }
+    }
+}
+
+/// An array element without a match on the other side, when comparing arrays without
+/// considering their order.
+#[derive(Debug, Clone, PartialEq)]
+enum Unmatched<'a> {
+    /// An extra element in lhs.
+    Lhs,
+    /// An element of rhs, and the path to the closest unpaired element in lhs, if any.
+    Rhs(Option<PathRef<'a>>),
 }
 
+/// Formatting with `{:#}` colors the message with ANSI escape codes, and a precision such as
+/// `{:.100}` cuts off longer values.
 impl fmt::Display for DifferenceRef<'_> {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        let json_to_string = |json: &Value| serde_json::to_string_pretty(json).unwrap();
+        let message = Message {
+            path: &self.path,
+            lhs: self.lhs,
+            rhs: self.expected_value(),
+            compare_mode: self.config.compare_mode,
+            unmatched: self.unmatched.as_ref().map(|unmatched| match unmatched {
+                Unmatched::Lhs => None,
+                Unmatched::Rhs(closest) => closest.as_ref(),
+            }),
+        };
+        fmt::Display::fmt(&message, f)
+    }
+}
 
-        match (&self.config.compare_mode, &self.lhs, &self.rhs) {
+/// Cut `value` off after `max` characters.
+fn elide(mut value: String, max: usize) -> String {
+    if let Some((idx, _)) = value.char_indices().nth(max) {
+        value.truncate(idx);
+        value.push('…');
+    }
+    value
+}
+
+/// The failure message for a difference, shared by [`DifferenceRef`] and [`Difference`].
+struct Message<'v, P> {
+    path: P,
+    lhs: Option<&'v Value>,
+    rhs: Option<&'v Value>,
+    compare_mode: CompareMode,
+    /// The path to the closest match, for unmatched array elements.
+    unmatched: Option<Option<P>>,
+}
+
+impl<P: fmt::Display> fmt::Display for Message<'_, P> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let precision = f.precision();
+        let json_to_string = |json: &Value| {
+            let json = serde_json::to_string_pretty(json).unwrap();
+            match precision {
+                Some(max) => elide(json, max),
+                None => json,
+            }
+        };
+        let painter = Painter {
+            color: f.alternate(),
+        };
+        let path = painter.path(&self.path);
+
+        if let Some(closest) = &self.unmatched {
+            let (actual, expected) = match self.compare_mode {
+                CompareMode::Inclusive => ("actual", "expected"),
+                CompareMode::Strict => ("lhs", "rhs"),
+            };
+            // Extra elements in lhs have nothing on the rhs side, while elements of rhs come with
+            // the closest element of lhs, if any.
+            let (value, side, closest_value, label, other) = match self.rhs {
+                None => (self.lhs, Side::Actual, None, actual, expected),
+                Some(_) => (self.rhs, Side::Expected, self.lhs, expected, actual),
+            };
+            let value = json_to_string(value.unwrap());
+            writeln!(
+                f,
+                "json atom at path \"{}\" has no match in {}:",
+                path, other
+            )?;
+            writeln!(f, "    {}:", label)?;
+            match (closest, closest_value) {
+                (Some(closest), Some(closest_value)) => {
+                    let closest_value = json_to_string(closest_value);
+                    let (changed, closest_changed) = color::changed(&value, &closest_value);
+                    writeln!(f, "{}", painter.value(&value, side, Some(changed), 8))?;
+                    writeln!(
+                        f,
+                        "    closest {} at path \"{}\":",
+                        other,
+                        painter.path(closest)
+                    )?;
+                    write!(
+                        f,
+                        "{}",
+                        painter.value(&closest_value, Side::Actual, Some(closest_changed), 8)
+                    )?;
+                }
+                _ => write!(f, "{}", painter.value(&value, side, None, 8))?,
+            }
+            return Ok(());
+        }
+
+        if let (Some(actual), Some(matcher)) = (self.lhs, self.rhs.and_then(Matcher::from_value)) {
+            let (expected_label, actual_label) = match self.compare_mode {
+                CompareMode::Inclusive => ("expected", "actual"),
+                CompareMode::Strict => ("rhs", "lhs"),
+            };
+            writeln!(f, "json atom at path \"{}\" does not match:", path)?;
+            writeln!(f, "    {}:", expected_label)?;
+            writeln!(
+                f,
+                "{}",
+                painter.value(&matcher.to_string(), Side::Expected, None, 8)
+            )?;
+            writeln!(f, "    {}:", actual_label)?;
+            write!(
+                f,
+                "{}",
+                painter.value(&json_to_string(actual), Side::Actual, None, 8)
+            )?;
+            return Ok(());
+        }
+
+        match (&self.compare_mode, &self.lhs, &self.rhs) {
             (CompareMode::Inclusive, Some(actual), Some(expected)) => {
-                writeln!(f, "json atoms at path \"{}\" are not equal:", self.path)?;
+                let (actual, expected) = (json_to_string(actual), json_to_string(expected));
+                let (actual_changed, expected_changed) = color::changed(&actual, &expected);
+                writeln!(f, "json atoms at path \"{}\" are not equal:", path)?;
                 writeln!(f, "    expected:")?;
-                writeln!(f, "{}", json_to_string(expected).indent(8))?;
+                writeln!(
+                    f,
+                    "{}",
+                    painter.value(&expected, Side::Expected, Some(expected_changed), 8)
+                )?;
                 writeln!(f, "    actual:")?;
-                write!(f, "{}", json_to_string(actual).indent(8))?;
-            }
-            (CompareMode::Inclusive, None, Some(_expected)) => {
                 write!(
                     f,
-                    "json atom at path \"{}\" is missing from actual",
-                    self.path
+                    "{}",
+                    painter.value(&actual, Side::Actual, Some(actual_changed), 8)
                 )?;
             }
+            (CompareMode::Inclusive, None, Some(_expected)) => {
+                write!(f, "json atom at path \"{}\" is missing from actual", path)?;
+            }
             (CompareMode::Inclusive, Some(_actual), None) => {
                 unreachable!("stuff missing actual wont produce an error")
             }
             (CompareMode::Inclusive, None, None) => unreachable!("can't both be missing"),
 
             (CompareMode::Strict, Some(lhs), Some(rhs)) => {
-                writeln!(f, "json atoms at path \"{}\" are not equal:", self.path)?;
+                let (lhs, rhs) = (json_to_string(lhs), json_to_string(rhs));
+                let (lhs_changed, rhs_changed) = color::changed(&lhs, &rhs);
+                writeln!(f, "json atoms at path \"{}\" are not equal:", path)?;
                 writeln!(f, "    lhs:")?;
-                writeln!(f, "{}", json_to_string(lhs).indent(8))?;
+                writeln!(
+                    f,
+                    "{}",
+                    painter.value(&lhs, Side::Actual, Some(lhs_changed), 8)
+                )?;
                 writeln!(f, "    rhs:")?;
-                write!(f, "{}", json_to_string(rhs).indent(8))?;
+                write!(
+                    f,
+                    "{}",
+                    painter.value(&rhs, Side::Expected, Some(rhs_changed), 8)
+                )?;
             }
             (CompareMode::Strict, None, Some(_)) => {
-                write!(f, "json atom at path \"{}\" is missing from lhs", self.path)?;
+                write!(f, "json atom at path \"{}\" is missing from lhs", path)?;
             }
             (CompareMode::Strict, Some(_), None) => {
-                write!(f, "json atom at path \"{}\" is missing from rhs", self.path)?;
+                write!(f, "json atom at path \"{}\" is missing from rhs", path)?;
             }
             (CompareMode::Strict, None, None) => unreachable!("can't both be missing"),
         }
@@ -377,6 +1421,34 @@ impl fmt::Display for DifferenceRef<'_> {
 }
 
 /// Represents a path to a JSON value in a tree structure.
+///
+/// Paths are displayed the way they appear in error messages, such as `.data.users[0].name`, and
+/// can be parsed back from that form or from an [RFC 6901] JSON Pointer such as
+/// `/data/users/0/name`. Fields that contain `.`, `[`, `]` or `"`, and a few other odd fields, are
+/// written as quoted JSON strings, as in `.data["user.name"]`:
+///
+/// ```
+/// use serde_json_assert::{Key, Path};
+///
+/// let path: Path = r#".data["user.name"][1]"#.parse().unwrap();
+/// assert_eq!(
+///     path,
+///     Path::Keys(vec![
+///         Key::Field("data".to_owned()),
+///         Key::Field("user.name".to_owned()),
+///         Key::Idx(1),
+///     ])
+/// );
+/// assert_eq!(path.to_string(), r#".data["user.name"][1]"#);
+/// assert_eq!(path.to_json_pointer(), "/data/user.name/1");
+/// assert_eq!("/data/user.name/1".parse::<Path>().unwrap(), path);
+/// ```
+///
+/// Paths are serialized as JSON Pointers. Like [`Path::from_json_pointer`], deserializing them
+/// turns fields named by an array index into [`Key::Idx`], and [`Key::Keyed`] elements come back
+/// as plain indexes.
+///
+/// [RFC 6901]: https://www.rfc-editor.org/rfc/rfc6901
 #[derive(Debug, Clone, PartialEq)]
 pub enum Path {
     /// The root of the JSON tree.
@@ -385,6 +1457,106 @@ pub enum Path {
     Keys(Vec<Key>),
 }
 
+impl Serialize for Path {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(&self.to_json_pointer())
+    }
+}
+
+impl<'de> Deserialize<'de> for Path {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let pointer = String::deserialize(deserializer)?;
+        Path::from_json_pointer(&pointer).map_err(de::Error::custom)
+    }
+}
+
+impl Path {
+    /// Parse an [RFC 6901](https://www.rfc-editor.org/rfc/rfc6901) JSON Pointer.
+    ///
+    /// Reference tokens that are valid array indexes, such as `0` or `12`, become [`Key::Idx`]
+    /// and all other tokens become [`Key::Field`]. [`Path::get`] still finds the field of an
+    /// object named by an index.
+    pub fn from_json_pointer(pointer: &str) -> Result<Path, ParsePathError> {
+        let tokens =
+            parse_pointer(pointer).map_err(|reason| ParsePathError::new(pointer, reason))?;
+        if tokens.is_empty() {
+            return Ok(Path::Root);
+        }
+        let keys = tokens
+            .into_iter()
+            .map(|token| match array_index(&token) {
+                Some(idx) => Key::Idx(idx),
+                None => Key::Field(token),
+            })
+            .collect();
+        Ok(Path::Keys(keys))
+    }
+
+    /// Format this path as an [RFC 6901](https://www.rfc-editor.org/rfc/rfc6901) JSON Pointer.
+    pub fn to_json_pointer(&self) -> String {
+        let mut pointer = String::new();
+        for key in self.keys() {
+            pointer.push('/');
+            match key {
+                Key::Field(field) => pointer.push_str(&field.replace('~', "~0").replace('/', "~1")),
+                Key::Idx(idx) | Key::Keyed { idx, .. } => pointer.push_str(&idx.to_string()),
+            }
+        }
+        pointer
+    }
+
+    /// Returns the value at this path in `value`, if there is one.
+    ///
+    /// ```
+    /// use serde_json_assert::Path;
+    /// use serde_json::json;
+    ///
+    /// let value = json!({ "users": [{ "name": "bob" }] });
+    /// let path: Path = ".users[0].name".parse().unwrap();
+    /// assert_eq!(path.get(&value), Some(&json!("bob")));
+    /// ```
+    pub fn get<'v>(&self, value: &'v Value) -> Option<&'v Value> {
+        self.keys()
+            .iter()
+            .try_fold(value, |value, key| match (key, value) {
+                (Key::Field(field), Value::Object(map)) => map.get(field),
+                (Key::Idx(idx) | Key::Keyed { idx, .. }, Value::Array(array)) => array.get(*idx),
+                (Key::Idx(idx), Value::Object(map)) => map.get(&idx.to_string()),
+                _ => None,
+            })
+    }
+
+    pub(crate) fn keys(&self) -> &[Key] {
+        match self {
+            Path::Root => &[],
+            Path::Keys(keys) => keys,
+        }
+    }
+}
+
+impl fmt::Display for Path {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Path::Root => write!(f, "(root)"),
+            Path::Keys(keys) => keys.iter().try_for_each(|key| write!(f, "{}", key)),
+        }
+    }
+}
+
+impl FromStr for Path {
+    type Err = ParsePathError;
+
+    fn from_str(path: &str) -> Result<Self, Self::Err> {
+        parse_path(path)
+    }
+}
+
 impl<'a> From<PathRef<'a>> for Path {
     fn from(path: PathRef<'a>) -> Self {
         match path {
@@ -395,13 +1567,13 @@ impl<'a> From<PathRef<'a>> for Path {
 }
 
 #[derive(Debug, Clone, PartialEq)]
-enum PathRef<'a> {
+pub(crate) enum PathRef<'a> {
     Root,
     Keys(Vec<KeyRef<'a>>),
 }
 
 impl<'a> PathRef<'a> {
-    fn append(&self, next: KeyRef<'a>) -> PathRef<'a> {
+    pub(crate) fn append(&self, next: KeyRef<'a>) -> PathRef<'a> {
         match self {
             PathRef::Root => PathRef::Keys(vec![next]),
             PathRef::Keys(list) => {
@@ -411,6 +1583,13 @@ impl<'a> PathRef<'a> {
             }
         }
     }
+
+    pub(crate) fn keys(&self) -> &[KeyRef<'a>] {
+        match self {
+            PathRef::Root => &[],
+            PathRef::Keys(keys) => keys,
+        }
+    }
 }
 
 impl fmt::Display for PathRef<'_> {
@@ -428,12 +1607,35 @@ impl fmt::Display for PathRef<'_> {
 }
 
 /// Represents a key in a JSON object or an index in a JSON array.
-#[derive(Debug, Clone, PartialEq, Eq)]
+///
+/// Keys are serialized as a number for [`Key::Idx`], a string for [`Key::Field`] and an object
+/// with `idx` and `id` members for [`Key::Keyed`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(untagged)]
+#[non_exhaustive]
 pub enum Key {
     /// An index in a JSON array.
     Idx(usize),
     /// A field in a JSON object.
     Field(String),
+    /// An element in a JSON array that was matched by key, see
+    /// [`Config::match_arrays_by_key`](crate::Config::match_arrays_by_key).
+    Keyed {
+        /// The index of the element in actual, or in expected if it is missing from actual.
+        idx: usize,
+        /// The key values of the element, such as `id=42`.
+        id: String,
+    },
+}
+
+impl fmt::Display for Key {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Key::Idx(idx) => write!(f, "[{}]", idx),
+            Key::Field(field) => write_field(f, field),
+            Key::Keyed { idx, id } => write!(f, "[{}:{}]", idx, id),
+        }
+    }
 }
 
 impl<'a> From<KeyRef<'a>> for Key {
@@ -441,26 +1643,60 @@ impl<'a> From<KeyRef<'a>> for Key {
         match key {
             KeyRef::Idx(idx) => Key::Idx(idx),
             KeyRef::Field(field) => Key::Field(field.to_owned()),
+            KeyRef::Keyed { idx, .. } => Key::Keyed {
+                idx,
+                id: key.keyed_id(),
+            },
         }
     }
 }
 
 #[derive(Debug, Copy, Clone, PartialEq)]
-enum KeyRef<'a> {
+pub(crate) enum KeyRef<'a> {
     Idx(usize),
     Field(&'a str),
+    Keyed {
+        idx: usize,
+        element: &'a Value,
+        keys: &'a [String],
+    },
+}
+
+impl<'a> KeyRef<'a> {
+    fn keyed(idx: usize, element: &'a Value, keys: &'a [String]) -> Self {
+        KeyRef::Keyed { idx, element, keys }
+    }
+
+    fn keyed_id(&self) -> String {
+        match self {
+            KeyRef::Keyed { element, keys, .. } => keys
+                .iter()
+                .map(|key| format!("{}={}", key, element[key.as_str()]))
+                .collect::<Vec<_>>()
+                .join(","),
+            _ => unreachable!("only keyed elements have an id"),
+        }
+    }
 }
 
 impl fmt::Display for KeyRef<'_> {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
             KeyRef::Idx(idx) => write!(f, "[{}]", idx),
-            KeyRef::Field(key) => write!(f, ".{}", key),
+            KeyRef::Field(key) => write_field(f, key),
+            KeyRef::Keyed { idx, .. } => write!(f, "[{}:{}]", idx, self.keyed_id()),
         }
     }
 }
 
 fn fold_json<'a>(json: &'a Value, folder: &mut DiffFolder<'a, '_>) {
+    if let Some(matcher) = Matcher::from_value(folder.rhs) {
+        return folder.on_matcher(json, matcher);
+    }
+    if let Some(literal) = matcher::unescape(folder.rhs) {
+        return folder.on_literal(json, literal);
+    }
+
     match json {
         Value::Null => folder.on_null(json),
         Value::Bool(_) => folder.on_bool(json),
@@ -693,4 +1929,660 @@ mod test {
         let diffs = diff(&json, &json, &config);
         assert_eq!(diffs, vec![]);
     }
+
+    #[test]
+    fn test_matchers() {
+        let config = Config::new(CompareMode::Inclusive);
+        let expected = json!({ "id": Matcher::AnyNumber, "name": Matcher::AnyString });
+
+        let actual = json!({ "id": 1, "name": "bob", "age": 42 });
+        let diffs = diff(&actual, &expected, &config);
+        assert_eq!(diffs, vec![]);
+
+        let actual = json!({ "id": "1", "name": "bob" });
+        let diffs = diff(&actual, &expected, &config);
+        assert_eq!(diffs.len(), 1);
+        let difference = Difference::from(diffs.into_iter().next().unwrap());
+        assert_eq!(difference.matcher(), Some(Matcher::AnyNumber));
+        assert_eq!(
+            difference.path(),
+            &Path::Keys(vec![Key::Field("id".to_owned())])
+        );
+
+        let actual = json!({ "name": "bob" });
+        let diffs = diff(&actual, &expected, &config);
+        assert_eq!(diffs.len(), 1);
+        let difference = Difference::from(diffs.into_iter().next().unwrap());
+        assert_eq!(difference.matcher(), None);
+        // Values missing from actual in inclusive mode come with the object they belong in.
+        assert_eq!(difference.expected(), &Some(expected.clone()));
+        assert_eq!(
+            difference.expected_value(),
+            Some(&json!(Matcher::AnyNumber))
+        );
+
+        let config = Config::new(CompareMode::Strict);
+        let expected = json!([Matcher::Any, Matcher::NotNull]);
+        let actual = json!([null, 1]);
+        let diffs = diff(&actual, &expected, &config);
+        assert_eq!(diffs, vec![]);
+
+        let actual = json!([1, null]);
+        let diffs = diff(&actual, &expected, &config);
+        assert_eq!(diffs.len(), 1);
+
+        // Escaped matchers are compared as plain strings.
+        let expected = json!({ "a": Matcher::escape("{{any}}") });
+        assert_eq!(diff(&json!({ "a": "{{any}}" }), &expected, &config), vec![]);
+        assert_eq!(diff(&json!({ "a": "x" }), &expected, &config).len(), 1);
+        assert_eq!(diff(&json!({ "a": 1 }), &expected, &config).len(), 1);
+
+        // An invalid regex in expected is reported rather than silently ignored.
+        let expected = json!({ "a": "{{regex:(}}" });
+        let actual = json!({ "a": "(" });
+        let diffs = diff(&actual, &expected, &config);
+        assert_eq!(diffs.len(), 1);
+        assert!(diffs[0]
+            .to_string()
+            .contains("a valid regex, but /(/ is invalid"));
+    }
+
+    #[test]
+    fn test_ignore_path() {
+        let config = Config::new(CompareMode::Strict)
+            .ignore_path(".meta.request_id")
+            .ignore_path(".items[*].created_at");
+
+        let lhs = json!({
+            "meta": { "request_id": "a", "version": 1 },
+            "items": [{ "id": 1, "created_at": 1 }, { "id": 2, "created_at": 2 }],
+        });
+        let rhs = json!({
+            "meta": { "request_id": "b", "version": 1 },
+            "items": [{ "id": 1, "created_at": 3 }, { "id": 2 }],
+        });
+        let diffs = diff(&lhs, &rhs, &config);
+        assert_eq!(diffs, vec![]);
+
+        let rhs = json!({
+            "meta": { "version": 2 },
+            "items": [{ "id": 1 }, { "id": 2 }],
+        });
+        let diffs = diff(&lhs, &rhs, &config);
+        assert_eq!(diffs.len(), 1);
+
+        let config = Config::new(CompareMode::Strict).ignore_path(".**.updated_at");
+        let lhs = json!({ "updated_at": 1, "a": [{ "updated_at": 2, "b": 1 }] });
+        let rhs = json!({ "a": [{ "updated_at": 3, "b": 1 }] });
+        let diffs = diff(&lhs, &rhs, &config);
+        assert_eq!(diffs, vec![]);
+
+        let config = Config::new(CompareMode::Inclusive).ignore_path("(root)");
+        let (lhs, rhs) = (json!(1), json!(2));
+        let diffs = diff(&lhs, &rhs, &config);
+        assert_eq!(diffs, vec![]);
+
+        let config = Config::new(CompareMode::Inclusive)
+            .consider_array_sorting(false)
+            .ignore_path("[*].created_at");
+        let actual = json!([{ "id": 2, "created_at": 1 }, { "id": 1, "created_at": 2 }]);
+        let expected = json!([{ "id": 1, "created_at": 3 }, { "id": 2, "created_at": 4 }]);
+        let diffs = diff(&actual, &expected, &config);
+        assert_eq!(diffs, vec![]);
+    }
+
+    #[test]
+    fn test_path_config() {
+        let config = Config::new(CompareMode::Strict)
+            .path_config(".metrics", |config| {
+                config.float_compare_mode(FloatCompareMode::Epsilon(1e-6))
+            })
+            .path_config(".tags", |config| config.consider_array_sorting(false))
+            .path_config(".extra", |config| {
+                config.compare_mode(CompareMode::Inclusive)
+            });
+
+        let lhs = json!({
+            "metrics": { "p50": 0.1000001, "p99": [0.2000001] },
+            "tags": ["b", "a"],
+            "extra": { "a": 1, "b": 2 },
+            "value": 1.0,
+        });
+        let rhs = json!({
+            "metrics": { "p50": 0.1, "p99": [0.2] },
+            "tags": ["a", "b"],
+            "extra": { "a": 1 },
+            "value": 1.0,
+        });
+        let diffs = diff(&lhs, &rhs, &config);
+        assert_eq!(diffs, vec![]);
+
+        // The overrides don't leak outside of their paths.
+        let lhs = json!({ "value": 1.0000001, "other": ["b", "a"] });
+        let rhs = json!({ "value": 1.0, "other": ["a", "b"] });
+        let diffs = diff(&lhs, &rhs, &config);
+        assert_eq!(diffs.len(), 3);
+
+        // The last matching path config wins.
+        let config = Config::new(CompareMode::Strict)
+            .path_config(".**", |config| config.compare_mode(CompareMode::Inclusive))
+            .path_config(".a", |config| config.compare_mode(CompareMode::Strict));
+        let lhs = json!({ "a": { "x": 1, "y": 2 }, "b": { "x": 1, "y": 2 } });
+        let rhs = json!({ "a": { "x": 1 }, "b": { "x": 1 } });
+        let diffs = diff(&lhs, &rhs, &config);
+        assert_eq!(diffs.len(), 1);
+        assert_eq!(diffs[0].path.to_string(), ".a.y");
+
+        // Including in nested objects below a narrower path config added last.
+        let lhs = json!({ "a": { "n": { "p": 1, "q": 2 } }, "b": { "n": { "p": 1, "q": 2 } } });
+        let rhs = json!({ "a": { "n": { "p": 1 } }, "b": { "n": { "p": 1 } } });
+        let diffs = diff(&lhs, &rhs, &config);
+        assert_eq!(diffs.len(), 1);
+        assert_eq!(diffs[0].path.to_string(), ".a.n.q");
+
+        // A broader path config added last overrides the narrower one.
+        let config = Config::new(CompareMode::Strict)
+            .path_config(".a", |config| config.compare_mode(CompareMode::Strict))
+            .path_config(".**", |config| config.compare_mode(CompareMode::Inclusive));
+        assert_eq!(diff(&lhs, &rhs, &config), vec![]);
+
+        // Path configs can be nested.
+        let config = Config::new(CompareMode::Strict).path_config(".a", |config| {
+            config
+                .compare_mode(CompareMode::Inclusive)
+                .path_config(".a.n", |config| config.compare_mode(CompareMode::Strict))
+        });
+        let lhs = json!({ "a": { "n": { "p": 1, "q": 2 }, "m": 1 } });
+        let rhs = json!({ "a": { "n": { "p": 1 } } });
+        let diffs = diff(&lhs, &rhs, &config);
+        assert_eq!(diffs.len(), 1);
+        assert_eq!(diffs[0].path.to_string(), ".a.n.q");
+    }
+
+    #[test]
+    fn test_array_keyed() {
+        let config = Config::new(CompareMode::Strict).match_arrays_by_key(["id"]);
+
+        let lhs = json!([{ "id": 1, "v": "a" }, { "id": 2, "v": "b" }]);
+        let rhs = json!([{ "id": 2, "v": "b" }, { "id": 1, "v": "a" }]);
+        let diffs = diff(&lhs, &rhs, &config);
+        assert_eq!(diffs, vec![]);
+
+        // An insertion only produces a single difference.
+        let lhs = json!([{ "id": 1 }, { "id": 2 }, { "id": 3, "v": "x" }]);
+        let rhs = json!([{ "id": 0 }, { "id": 1 }, { "id": 2 }, { "id": 3, "v": "y" }]);
+        let diffs = diff(&lhs, &rhs, &config);
+        let paths = diffs.iter().map(|d| d.path.to_string()).collect::<Vec<_>>();
+        assert_eq!(paths, vec!["[0:id=0]", "[2:id=3].v"]);
+        assert_eq!(diffs[0].lhs, None);
+        assert_eq!(
+            Path::from(diffs[1].path.clone()),
+            Path::Keys(vec![
+                Key::Keyed {
+                    idx: 2,
+                    id: "id=3".to_owned()
+                },
+                Key::Field("v".to_owned())
+            ])
+        );
+
+        // Extra elements are only reported in strict mode.
+        let lhs = json!([{ "id": 1 }, { "id": 2 }]);
+        let rhs = json!([{ "id": 1 }]);
+        let diffs = diff(&lhs, &rhs, &config);
+        assert_eq!(diffs.len(), 1);
+        assert_eq!(diffs[0].path.to_string(), "[1:id=2]");
+        assert_eq!(diffs[0].rhs, None);
+        let config = config.compare_mode(CompareMode::Inclusive);
+        let diffs = diff(&lhs, &rhs, &config);
+        assert_eq!(diffs, vec![]);
+
+        // Composite keys and elements without a key.
+        let config = Config::new(CompareMode::Strict).match_arrays_by_key(["id", "region"]);
+        let lhs = json!([{ "id": 1, "region": "eu" }, { "id": 1, "region": "us" }, 3]);
+        let rhs = json!([{ "id": 1, "region": "us" }, { "id": 1, "region": "eu" }, 3]);
+        let diffs = diff(&lhs, &rhs, &config);
+        assert_eq!(diffs, vec![]);
+
+        // Elements without a key are paired by position among themselves.
+        let lhs = json!([3, { "id": 1, "region": "eu" }, 4]);
+        let rhs = json!([{ "id": 1, "region": "eu" }, 3, 5]);
+        let diffs = diff(&lhs, &rhs, &config);
+        let paths = diffs.iter().map(|d| d.path.to_string()).collect::<Vec<_>>();
+        assert_eq!(paths, vec!["[2]"]);
+        assert_eq!(diffs[0].lhs, Some(&json!(4)));
+        assert_eq!(diffs[0].rhs, Some(&json!(5)));
+
+        // Key values are compared with the configured comparison.
+        let config = Config::new(CompareMode::Strict)
+            .numeric_mode(NumericMode::AssumeFloat)
+            .match_arrays_by_key(["id"]);
+        let lhs = json!([{ "id": 2, "v": "b" }, { "id": 1, "v": "a" }]);
+        let rhs = json!([{ "id": 1.0, "v": "a" }, { "id": 2.0, "v": "b" }]);
+        assert_eq!(diff(&lhs, &rhs, &config), vec![]);
+        let rhs = json!([{ "id": Matcher::AnyNumber, "v": "b" }, { "id": 1, "v": "a" }]);
+        assert_eq!(diff(&lhs, &rhs, &config), vec![]);
+    }
+
+    #[test]
+    fn test_array_aligned() {
+        let config = Config::new(CompareMode::Strict).align_arrays(true);
+
+        let lhs = json!([1, 2, 3, 4, 5]);
+        let diffs = diff(&lhs, &lhs, &config);
+        assert_eq!(diffs, vec![]);
+
+        // A single insertion is reported once instead of shifting every element after it.
+        let lhs = json!([1, 2, 4, 5, 6]);
+        let rhs = json!([1, 2, 3, 4, 5, 6]);
+        let diffs = diff(&lhs, &rhs, &config);
+        assert_eq!(diffs.len(), 1);
+        assert_eq!(diffs[0].path.to_string(), "[2]");
+        assert_eq!((diffs[0].lhs, diffs[0].rhs), (None, Some(&json!(3))));
+
+        // Deletions are reported at their index in lhs.
+        let diffs = diff(&rhs, &lhs, &config);
+        assert_eq!(diffs.len(), 1);
+        assert_eq!(diffs[0].path.to_string(), "[2]");
+        assert_eq!((diffs[0].lhs, diffs[0].rhs), (Some(&json!(3)), None));
+
+        // Changed elements are compared recursively.
+        let lhs = json!([{ "a": 1 }, { "a": 2, "b": 1 }, { "a": 3 }]);
+        let rhs = json!([{ "a": 0 }, { "a": 1 }, { "a": 2, "b": 2 }, { "a": 3 }]);
+        let diffs = diff(&lhs, &rhs, &config);
+        let paths = diffs.iter().map(|d| d.path.to_string()).collect::<Vec<_>>();
+        assert_eq!(paths, vec!["[0]", "[1].b"]);
+
+        // Extra elements are allowed in inclusive mode.
+        let config = Config::new(CompareMode::Inclusive).align_arrays(true);
+        let lhs = json!([0, 1, 2, 3]);
+        let rhs = json!([1, 3]);
+        let diffs = diff(&lhs, &rhs, &config);
+        assert_eq!(diffs, vec![]);
+        let rhs = json!([1, 4, 3]);
+        let diffs = diff(&lhs, &rhs, &config);
+        assert_eq!(diffs.len(), 1);
+        assert_eq!(diffs[0].path.to_string(), "[2]");
+        assert_eq!(
+            (diffs[0].lhs, diffs[0].rhs),
+            (Some(&json!(2)), Some(&json!(4)))
+        );
+
+        // Turning alignment off only undoes alignment.
+        let config = Config::new(CompareMode::Inclusive)
+            .consider_array_sorting(false)
+            .align_arrays(false);
+        assert_eq!(config.array_sorting_mode, ArraySortingMode::Ignore);
+        let config = config.align_arrays(true).align_arrays(false);
+        assert_eq!(config.array_sorting_mode, ArraySortingMode::Consider);
+    }
+
+    #[test]
+    fn test_array_contains_reports_elements() {
+        let config = Config::new(CompareMode::Inclusive).consider_array_sorting(false);
+
+        let lhs = json!([{ "id": 1, "v": "a" }, { "id": 2, "v": "b" }, { "id": 3, "v": "c" }]);
+        let rhs = json!([{ "id": 3, "v": "c" }, { "id": 2, "v": "x" }, { "id": 4, "v": "d" }]);
+        let diffs = diff(&lhs, &rhs, &config);
+        assert_eq!(diffs.len(), 2);
+
+        // Unmatched expected elements are reported at their index in expected, together with the
+        // closest unpaired element in actual.
+        assert_eq!(diffs[0].path.to_string(), "[1]");
+        assert_eq!(diffs[0].rhs, Some(&rhs[1]));
+        assert_eq!(diffs[0].lhs, Some(&lhs[1]));
+        let difference = Difference::from(diffs.into_iter().next().unwrap());
+        assert!(difference.is_unmatched());
+        assert_eq!(
+            difference.closest_match(),
+            Some(&Path::Keys(vec![Key::Idx(1)]))
+        );
+
+        // Extra elements are only reported in strict mode.
+        let config = Config::new(CompareMode::Strict).consider_array_sorting(false);
+        let lhs = json!([1, 2, 3]);
+        let rhs = json!([3, 1]);
+        let diffs = diff(&lhs, &rhs, &config);
+        assert_eq!(diffs.len(), 1);
+        assert_eq!(diffs[0].path.to_string(), "[1]");
+        assert_eq!((diffs[0].lhs, diffs[0].rhs), (Some(&json!(2)), None));
+        assert_eq!(diffs[0].unmatched, Some(Unmatched::Lhs));
+
+        // Duplicates have to be matched as many times as they occur.
+        // The closest element is paired with the unmatched one, so it isn't reported again.
+        let lhs = json!([1, 1, 2]);
+        let rhs = json!([1, 2, 2]);
+        let diffs = diff(&lhs, &rhs, &config);
+        assert_eq!(diffs.len(), 1);
+        assert_eq!(diffs[0].path.to_string(), "[2]");
+        assert_eq!(
+            (diffs[0].lhs, diffs[0].rhs),
+            (Some(&json!(1)), Some(&json!(2)))
+        );
+    }
+
+    #[test]
+    fn test_array_contains_finds_maximum_matching() {
+        let config = Config::new(CompareMode::Inclusive).consider_array_sorting(false);
+
+        // `{{any}}` matches both actual elements, so pairing it with the first one would leave
+        // `"a"` without a match.
+        let lhs = json!(["a", 1]);
+        let rhs = json!([Matcher::Any, "a"]);
+        assert_eq!(diff(&lhs, &rhs, &config), vec![]);
+
+        let config = config
+            .numeric_mode(NumericMode::AssumeFloat)
+            .float_compare_mode(FloatCompareMode::Epsilon(0.5));
+        let lhs = json!([1.0, 1.4]);
+        let rhs = json!([1.2, 0.9]);
+        assert_eq!(diff(&lhs, &rhs, &config), vec![]);
+    }
+
+    #[test]
+    fn test_serde() {
+        let path = Path::Keys(vec![
+            Key::Field("a/b".to_owned()),
+            Key::Idx(1),
+            Key::Field("01".to_owned()),
+        ]);
+        assert_eq!(serde_json::to_value(&path).unwrap(), json!("/a~1b/1/01"));
+        assert_eq!(
+            serde_json::from_value::<Path>(json!("/a~1b/1/01")).unwrap(),
+            path
+        );
+        assert_eq!(
+            serde_json::from_value::<Path>(json!("")).unwrap(),
+            Path::Root
+        );
+        assert!(serde_json::from_value::<Path>(json!("a")).is_err());
+
+        let keys = vec![
+            Key::Idx(1),
+            Key::Field("a".to_owned()),
+            Key::Keyed {
+                idx: 2,
+                id: "id=3".to_owned(),
+            },
+        ];
+        let value = json!([1, "a", { "idx": 2, "id": "id=3" }]);
+        assert_eq!(serde_json::to_value(&keys).unwrap(), value);
+        assert_eq!(serde_json::from_value::<Vec<Key>>(value).unwrap(), keys);
+
+        let config = Config::new(CompareMode::Strict).consider_array_sorting(false);
+        let lhs = json!({ "a": null, "b": [1, 2] });
+        let rhs = json!({ "b": [1, 3] });
+        let mut differences = diff(&lhs, &rhs, &config)
+            .into_iter()
+            .map(Difference::from)
+            .collect::<Vec<_>>();
+        differences.sort_by_key(|difference| serde_json::to_string(difference.path()).unwrap());
+        let value = serde_json::to_value(&differences).unwrap();
+        assert_eq!(
+            value,
+            json!([
+                { "path": "/a", "kind": "unexpected_in_actual", "actual": null, "mode": "strict" },
+                {
+                    "path": "/b/1",
+                    "kind": "unmatched_element",
+                    "actual": 2,
+                    "expected": 3,
+                    "mode": "strict",
+                    "unmatched": true,
+                    "closest_match": "/b/1",
+                },
+            ])
+        );
+
+        let read = serde_json::from_value::<Vec<Difference>>(value.clone()).unwrap();
+        assert_eq!(read[0].actual(), &Some(Value::Null));
+        assert_eq!(read[0].expected(), &None);
+        assert!(read[1].is_unmatched());
+        assert_eq!(read[1].closest_match(), differences[1].closest_match());
+        assert_eq!(read[1].config().compare_mode, CompareMode::Strict);
+        assert_eq!(serde_json::to_value(&read).unwrap(), value);
+
+        // Missing values are read back into a container of their own.
+        let config = Config::new(CompareMode::Inclusive);
+        let lhs = json!({ "a": {}, "b": [1] });
+        let rhs = json!({ "a": { "c": 1, "d": 2 }, "b": [1, 2] });
+        for difference in diff(&lhs, &rhs, &config) {
+            let difference = Difference::from(difference);
+            let value = serde_json::to_value(&difference).unwrap();
+            let read = serde_json::from_value::<Difference>(value.clone()).unwrap();
+            assert_eq!(read.expected_value(), difference.expected_value());
+            assert_eq!(read.to_string(), difference.to_string());
+            assert_eq!(serde_json::to_value(&read).unwrap(), value);
+        }
+        let value = json!({ "path": "/b/1", "kind": "missing_in_actual", "expected": 2, "mode": "inclusive" });
+        let read = serde_json::from_value::<Difference>(value).unwrap();
+        assert_eq!(read.expected(), &Some(json!([null, 2])));
+    }
+
+    #[test]
+    fn test_path_display_and_parse() {
+        let path = Path::Keys(vec![
+            Key::Field("a".to_owned()),
+            Key::Field("b.c".to_owned()),
+            Key::Idx(0),
+            Key::Field("[d]".to_owned()),
+            Key::Field(String::new()),
+        ]);
+        let displayed = r#".a["b.c"][0]["[d]"][""]"#;
+        assert_eq!(path.to_string(), displayed);
+        assert_eq!(displayed.parse::<Path>().unwrap(), path);
+        assert_eq!(path.to_json_pointer(), "/a/b.c/0/[d]/");
+        assert_eq!(Path::from_json_pointer("/a/b.c/0/[d]/").unwrap(), path);
+
+        assert_eq!(Path::Root.to_string(), "(root)");
+        assert_eq!("(root)".parse::<Path>().unwrap(), Path::Root);
+        assert_eq!("".parse::<Path>().unwrap(), Path::Root);
+        assert_eq!(
+            ".a[*]".parse::<Path>().unwrap_err().to_string(),
+            r#"invalid path ".a[*]": wildcards are not allowed in a path"#
+        );
+        assert!("/a~2".parse::<Path>().is_err());
+
+        let keyed = Path::Keys(vec![Key::Keyed {
+            idx: 1,
+            id: "id=2".to_owned(),
+        }]);
+        assert_eq!(keyed.to_string(), "[1:id=2]");
+        assert_eq!(keyed.to_string().parse::<Path>().unwrap(), keyed);
+        assert_eq!(keyed.to_json_pointer(), "/1");
+
+        // Paths in messages are escaped the same way.
+        let lhs = json!({ "a.b": 1 });
+        let rhs = json!({ "a.b": 2 });
+        let config = Config::new(CompareMode::Strict);
+        let diffs = diff(&lhs, &rhs, &config);
+        assert_eq!(diffs[0].path.to_string(), r#"["a.b"]"#);
+    }
+
+    #[test]
+    fn test_path_get() {
+        let value = json!({ "a": [{ "b": 1 }], "0": "zero" });
+        let get = |path: &str| path.parse::<Path>().unwrap().get(&value).cloned();
+        assert_eq!(get(".a[0].b"), Some(json!(1)));
+        assert_eq!(get("/a/0/b"), Some(json!(1)));
+        assert_eq!(get("(root)"), Some(value.clone()));
+        assert_eq!(get("/0"), Some(json!("zero")));
+        assert_eq!(get(".a[1]"), None);
+        assert_eq!(get(".a.b"), None);
+    }
+
+    #[test]
+    fn test_difference_kind_and_display() {
+        let kinds = |lhs: Value, rhs: Value, config: &Config| {
+            let mut kinds = diff(&lhs, &rhs, config)
+                .iter()
+                .map(|diff| (diff.path.to_string(), diff.kind()))
+                .collect::<Vec<_>>();
+            kinds.sort_by(|a, b| a.0.cmp(&b.0));
+            kinds
+        };
+        let config = Config::new(CompareMode::Strict);
+        assert_eq!(
+            kinds(
+                json!({ "a": 1, "b": [1], "c": "x", "d": true }),
+                json!({ "a": 1.5, "b": [1, 2], "c": Matcher::AnyNumber, "e": null }),
+                &config
+            ),
+            vec![
+                (".a".to_owned(), DifferenceKind::ValueMismatch),
+                (".b[1]".to_owned(), DifferenceKind::ArrayLengthMismatch),
+                (".c".to_owned(), DifferenceKind::MatcherMismatch),
+                (".d".to_owned(), DifferenceKind::UnexpectedInActual),
+                (".e".to_owned(), DifferenceKind::MissingInActual),
+            ]
+        );
+        assert_eq!(
+            kinds(
+                json!([1, "2"]),
+                json!(["1", 3]),
+                &config.clone().align_arrays(true)
+            ),
+            vec![
+                ("[0]".to_owned(), DifferenceKind::TypeMismatch),
+                ("[1]".to_owned(), DifferenceKind::TypeMismatch),
+            ]
+        );
+        assert_eq!(
+            kinds(
+                json!([1]),
+                json!([2]),
+                &Config::new(CompareMode::Inclusive).consider_array_sorting(false)
+            ),
+            vec![("[0]".to_owned(), DifferenceKind::UnmatchedElement)]
+        );
+        // Elements missing from arrays matched by key aren't length mismatches.
+        assert_eq!(
+            kinds(
+                json!([{ "id": 1 }]),
+                json!([{ "id": 1 }, { "id": 2 }]),
+                &config.clone().match_arrays_by_key(["id"])
+            ),
+            vec![("[1:id=2]".to_owned(), DifferenceKind::MissingInActual)]
+        );
+
+        // The owned difference is displayed like the one it was created from.
+        let lhs = json!({ "a": [1, { "b": "x" }], "c.d": 1 });
+        let rhs = json!({ "a": [1, { "b": "y" }, 3], "c.d": 2 });
+        for config in [
+            Config::new(CompareMode::Strict),
+            Config::new(CompareMode::Inclusive).consider_array_sorting(false),
+        ] {
+            for diff in diff(&lhs, &rhs, &config) {
+                let message = diff.to_string();
+                assert_eq!(Difference::from(diff).to_string(), message);
+            }
+        }
+    }
+
+    #[test]
+    fn test_max_differences() {
+        let actual = json!({ "a": [1, 2, 3], "b": { "c": 1, "d": 2 }, "e": 1 });
+        let expected = json!({ "a": [4, 5], "b": { "c": 3, "f": 2 }, "g": 1 });
+        let config = Config::new(CompareMode::Strict);
+        let (diffs, skipped) = diff_counted(&actual, &expected, &config);
+        assert_eq!(skipped, 0);
+        let paths = diffs
+            .iter()
+            .map(|diff| diff.path.to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            paths,
+            vec![".a[0]", ".a[1]", ".a[2]", ".b.c", ".b.f", ".b.d", ".g", ".e"]
+        );
+
+        let config = config.max_differences(3);
+        let (diffs, skipped) = diff_counted(&actual, &expected, &config);
+        assert_eq!(diffs.len(), 3);
+        assert_eq!(diffs[2].path.to_string(), ".a[2]");
+        assert_eq!(skipped, 5);
+        assert_eq!(diff(&actual, &expected, &config).len(), 3);
+        assert_eq!(
+            diff_counted(&actual, &expected, &config.max_differences(8)).1,
+            0
+        );
+
+        // Unordered arrays are still compared in full to find matching elements, and only the
+        // reported elements get a closest match, without changing how many are counted.
+        let config = Config::new(CompareMode::Strict).consider_array_sorting(false);
+        let actual = json!([{ "a": 1 }, { "a": 2 }, { "a": 3 }, 1]);
+        let expected = json!([1, { "a": 4 }, { "a": 5 }, { "a": 6 }, 2]);
+        assert_eq!(diff(&actual, &expected, &config).len(), 4);
+        let config = config.max_differences(1);
+        let (diffs, skipped) = diff_counted(&actual, &expected, &config);
+        assert_eq!(diffs.len(), 1);
+        assert!(matches!(diffs[0].unmatched, Some(Unmatched::Rhs(Some(_)))));
+        assert_eq!(skipped, 3);
+        assert_eq!(
+            diff_counted(&json!([1, 2, 3]), &json!([3, 1, 2]), &config).1,
+            0
+        );
+    }
+
+    #[test]
+    fn test_elide() {
+        assert_eq!(elide("abc".to_owned(), 3), "abc");
+        assert_eq!(elide("abcd".to_owned(), 3), "abc…");
+        assert_eq!(elide("\"åäö\"".to_owned(), 2), "\"å…");
+        assert_eq!(elide("[1]".to_owned(), 0), "…");
+    }
+
+    #[test]
+    fn test_matches_stops_at_first_difference() {
+        let config = Config::new(CompareMode::Strict);
+        let actual = json!({ "a": [1, 2, 3], "b": { "c": 1 } });
+        let expected = json!({ "a": [4, 5, 6], "b": { "c": 2 } });
+        assert!(!matches(&actual, &expected, &config));
+        assert!(matches(&actual, &actual, &config));
+
+        let mut acc = Differences::first();
+        diff_with(
+            &actual,
+            &expected,
+            &config,
+            &config,
+            PathRef::Root,
+            &mut acc,
+        );
+        assert!(acc.found.is_empty());
+        assert_eq!(acc.skipped, 1);
+
+        let config = config.ignore_path(".**");
+        assert!(matches(&actual, &expected, &config));
+        let config = Config::new(CompareMode::Inclusive).consider_array_sorting(false);
+        assert!(matches(&json!([3, 2, 1, 0]), &json!([1, 2, 3]), &config));
+        assert!(!matches(&json!([3, 2]), &json!([1, 2]), &config));
+    }
+
+    #[test]
+    fn test_loops_stop_at_first_difference() {
+        let first_difference = |lhs: Value, rhs: Value, config: &Config| {
+            let mut acc = Differences::first();
+            diff_with(&lhs, &rhs, config, config, PathRef::Root, &mut acc);
+            (acc.found.len(), acc.skipped)
+        };
+        let config = Config::new(CompareMode::Strict);
+        for (lhs, rhs) in [
+            (json!({}), json!({ "a": 1, "b": 2, "c": 3 })),
+            (json!({ "a": 1, "b": 2, "c": 3 }), json!({})),
+            (json!([]), json!([1, 2, 3])),
+            (json!([1, 2, 3]), json!([])),
+        ] {
+            assert_eq!(first_difference(lhs, rhs, &config), (0, 1));
+        }
+
+        let config = config.consider_array_sorting(false);
+        assert_eq!(
+            first_difference(json!([1, 2, 3]), json!([4, 5, 6]), &config),
+            (0, 1)
+        );
+        assert_eq!(
+            first_difference(json!([1, 2, 3]), json!([]), &config),
+            (0, 1)
+        );
+    }
 }
@@ -0,0 +1,78 @@
+use crate::Difference;
+use std::{error, fmt};
+
+/// An error returned by [`try_assert_json_matches`](crate::try_assert_json_matches).
+///
+/// ```
+/// use serde_json_assert::{try_assert_json_matches, CompareMode, Config, Error};
+/// use serde_json::json;
+/// use std::collections::HashMap;
+///
+/// let config = Config::new(CompareMode::Strict);
+///
+/// let err = try_assert_json_matches(&json!({ "a": 1 }), &json!({ "a": 2 }), &config).unwrap_err();
+/// assert!(matches!(err, Error::Differences(_)));
+/// assert_eq!(err.differences().len(), 1);
+///
+/// // JSON objects can only have string keys.
+/// let map = HashMap::from([((1, 2), 3)]);
+/// let err = try_assert_json_matches(&map, &json!({}), &config).unwrap_err();
+/// assert!(matches!(err, Error::SerializeLhs(_)));
+/// ```
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum Error {
+    /// The values differ.
+    Differences(Vec<Difference>),
+    /// The left hand side, or actual, value could not be converted to JSON.
+    SerializeLhs(serde_json::Error),
+    /// The right hand side, or expected, value could not be converted to JSON.
+    SerializeRhs(serde_json::Error),
+}
+
+impl Error {
+    /// Returns the differences between the values, or an empty slice if they could not be
+    /// compared.
+    pub fn differences(&self) -> &[Difference] {
+        match self {
+            Error::Differences(differences) => differences,
+            _ => &[],
+        }
+    }
+}
+
+/// Displays the same message as the assertion macros do.
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::Differences(differences) => {
+                for (idx, difference) in differences.iter().enumerate() {
+                    if idx > 0 {
+                        write!(f, "\n\n")?;
+                    }
+                    write!(f, "{}", difference)?;
+                }
+                Ok(())
+            }
+            Error::SerializeLhs(err) => write!(
+                f,
+                "Couldn't convert left hand side value to JSON. Serde error: {}",
+                err
+            ),
+            Error::SerializeRhs(err) => write!(
+                f,
+                "Couldn't convert right hand side value to JSON. Serde error: {}",
+                err
+            ),
+        }
+    }
+}
+
+impl error::Error for Error {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            Error::Differences(_) => None,
+            Error::SerializeLhs(err) | Error::SerializeRhs(err) => Some(err),
+        }
+    }
+}
@@ -135,6 +135,24 @@
 //! ```text
 //! json atom at path ".a.b" is missing from lhs
 //! ```
+//!
+//! ## Negative assertions
+//!
+//! [`assert_json_ne`](macro.assert_json_ne.html) and
+//! [`assert_json_not_include`](macro.assert_json_not_include.html) fail if the values *do*
+//! match, for example to check that an update changed a document or that a sensitive field was
+//! removed:
+//!
+//! ```
+//! use serde_json_assert::{assert_json_not_include, Matcher};
+//! use serde_json::json;
+//!
+//! assert_json_not_include!(
+//!     actual: json!({ "id": 1, "name": "bob" }),
+//!     expected: json!({ "password": Matcher::Any }),
+//! )
+//! ```
+//!
 //! ## Custom message
 //!
 //! Like [`assert!`], the `assert_json` macros all have second forms, where a
@@ -157,6 +175,72 @@
 //! )
 //! ```
 //!
+//! The `debug_assert_json_*` macros, such as
+//! [`debug_assert_json_eq`](macro.debug_assert_json_eq.html), accept the same arguments and are
+//! only enabled in builds with debug assertions, like [`debug_assert!`].
+//!
+//! ## Matchers
+//!
+//! Values that are generated at runtime, such as IDs and timestamps, can be matched by type
+//! instead of by value by placing a [`Matcher`] in the expected value:
+//!
+//! ```
+//! use serde_json_assert::{assert_json_eq, Matcher};
+//! use serde_json::json;
+//!
+//! assert_json_eq!(
+//!     json!({
+//!         "id": 42,
+//!         "created_at": "2025-06-13T12:00:00Z",
+//!         "deleted_at": null,
+//!     }),
+//!     json!({
+//!         "id": Matcher::AnyNumber,
+//!         "created_at": Matcher::AnyString,
+//!         "deleted_at": Matcher::Any,
+//!     })
+//! )
+//! ```
+//!
+//! Strings can also be checked against a regular expression using [`Matcher::regex`]. If a value
+//! doesn't match, the error message names the matcher:
+//!
+//! ```text
+//! json atom at path ".id" does not match:
+//!     expected:
+//!         any number
+//!     actual:
+//!         "42"
+//! ```
+//!
+//! ## Colored output
+//!
+//! With the `color` feature enabled, the assertion macros highlight the path, the expected and
+//! actual values, and the characters that differ between them. Colors are only used when stderr
+//! is a terminal and the `NO_COLOR` environment variable is not set.
+//!
+//! ```toml
+//! [dev-dependencies]
+//! serde-json-assert = { version = "0.4.0", features = ["color"] }
+//! ```
+//!
+//! Messages returned by [`assert_json_matches_no_panic`] are never colored.
+//!
+//! ## Snapshots
+//!
+//! [`assert_json_snapshot`](macro.assert_json_snapshot.html) compares a value against the JSON
+//! stored in a file, relative to the root of your crate:
+//!
+//! ```no_run
+//! use serde_json_assert::assert_json_snapshot;
+//! use serde_json::json;
+//!
+//! assert_json_snapshot!(json!({ "id": 42, "name": "bob" }), "tests/snapshots/user.json");
+//! ```
+//!
+//! Run the tests with `SERDE_JSON_ASSERT_UPDATE=1` to create missing snapshots and rewrite the
+//! ones that don't match. Matchers in a rewritten snapshot are kept where they still match.
+//!
 //! ## Further customization
 //!
 //! You can use [`assert_json_matches`] to further customize the comparison.
@@ -175,13 +259,40 @@
     unknown_lints
 )]
 
-use diff::diff;
+use crate::color::Painter;
+use crate::core_ext::Indent;
+use crate::diff::{DifferenceRef, PathRef};
+use crate::unified::unified_diff;
+use diff::{diff, diff_counted, matches};
 use serde::Serialize;
-
-pub use crate::diff::{Difference, Key, Path};
-
+use serde_json::Value;
+use std::fmt;
+
+pub use crate::diff::{Difference, DifferenceKind, Key, Path};
+pub use crate::error::Error;
+pub use crate::matcher::{Matcher, RegexPattern};
+pub use crate::patch::{
+    apply_json_patch, json_merge_patch, json_patch, try_assert_json_matches_patched, PatchError,
+    PatchedError,
+};
+pub use crate::pattern::{ParsePathError, PathPattern};
+pub use crate::snapshot::{
+    __assert_json_snapshot_message, try_assert_json_snapshot, SnapshotAssertError, SnapshotError,
+};
+pub use crate::soft::SoftAssertions;
+
+mod align;
+mod color;
 mod core_ext;
 mod diff;
+mod error;
+mod matcher;
+mod matching;
+mod patch;
+mod pattern;
+mod snapshot;
+mod soft;
+mod unified;
 
 /// Assert that a JSON value contains other JSON value
 ///
@@ -317,12 +428,222 @@ macro_rules! assert_json_eq {
 #[macro_export]
 macro_rules! assert_json_matches {
     ($lhs:expr, $rhs:expr, $config:expr $(,)?) => {{
-        if let Err(error) = $crate::assert_json_matches_no_panic(&$lhs, &$rhs, $config) {
+        if let Err(error) = $crate::__assert_json_matches_message(&$lhs, &$rhs, $config) {
             panic!("\n{}", error);
         }
     }};
     ($lhs:expr, $rhs:expr, $config:expr, $($arg:tt)+) => {{
-        if let Err(error) = $crate::assert_json_matches_no_panic(&$lhs, &$rhs, $config) {
+        if let Err(error) = $crate::__assert_json_matches_message(&$lhs, &$rhs, $config) {
+            panic!("\n{}\n\n{}", format_args!($($arg)+), error);
+        }
+    }};
+}
+
+/// Like [`assert_json_eq`](macro.assert_json_eq.html), but only enabled in builds with debug
+/// assertions, like [`debug_assert_eq!`].
+///
+/// ```
+/// use serde_json_assert::debug_assert_json_eq;
+/// use serde_json::json;
+///
+/// debug_assert_json_eq!(json!({ "a": 1 }), json!({ "a": 1 }), "a is {}", 1);
+/// ```
+#[macro_export]
+macro_rules! debug_assert_json_eq {
+    ($($arg:tt)*) => {
+        if cfg!(debug_assertions) {
+            $crate::assert_json_eq!($($arg)*);
+        }
+    };
+}
+
+/// Like [`assert_json_include`](macro.assert_json_include.html), but only enabled in builds with
+/// debug assertions, like [`debug_assert!`].
+///
+/// ```
+/// use serde_json_assert::debug_assert_json_include;
+/// use serde_json::json;
+///
+/// debug_assert_json_include!(actual: json!({ "a": 1, "b": 2 }), expected: json!({ "a": 1 }));
+/// ```
+#[macro_export]
+macro_rules! debug_assert_json_include {
+    ($($arg:tt)*) => {
+        if cfg!(debug_assertions) {
+            $crate::assert_json_include!($($arg)*);
+        }
+    };
+}
+
+/// Like [`assert_json_contains`](macro.assert_json_contains.html), but only enabled in builds
+/// with debug assertions, like [`debug_assert!`].
+///
+/// ```
+/// use serde_json_assert::debug_assert_json_contains;
+/// use serde_json::json;
+///
+/// debug_assert_json_contains!(container: json!([1, 2, 3]), contained: json!([3, 1]));
+/// ```
+#[macro_export]
+macro_rules! debug_assert_json_contains {
+    ($($arg:tt)*) => {
+        if cfg!(debug_assertions) {
+            $crate::assert_json_contains!($($arg)*);
+        }
+    };
+}
+
+/// Like [`assert_json_matches`](macro.assert_json_matches.html), but only enabled in builds with
+/// debug assertions, like [`debug_assert!`].
+///
+/// ```
+/// use serde_json_assert::{debug_assert_json_matches, CompareMode, Config, NumericMode};
+/// use serde_json::json;
+///
+/// let config = Config::new(CompareMode::Strict).numeric_mode(NumericMode::AssumeFloat);
+/// debug_assert_json_matches!(json!([1, 2.0]), json!([1.0, 2]), &config);
+/// ```
+#[macro_export]
+macro_rules! debug_assert_json_matches {
+    ($($arg:tt)*) => {
+        if cfg!(debug_assertions) {
+            $crate::assert_json_matches!($($arg)*);
+        }
+    };
+}
+
+/// Assert that two JSON values are not equal.
+///
+/// The values are compared like [`assert_json_eq`](macro.assert_json_eq.html) does, and the
+/// assertion fails if there are no differences between them.
+///
+/// ```
+/// use serde_json_assert::assert_json_ne;
+/// use serde_json::json;
+///
+/// let before = json!({ "name": "bob", "version": 1 });
+/// let after = json!({ "name": "rob", "version": 2 });
+///
+/// assert_json_ne!(before, after);
+/// ```
+#[macro_export]
+macro_rules! assert_json_ne {
+    ($lhs:expr, $rhs:expr $(,)?) => {{
+        let config = $crate::Config::new($crate::CompareMode::Strict);
+        $crate::assert_json_not_matches!($lhs, $rhs, &config)
+    }};
+    ($lhs:expr, $rhs:expr, $($arg:tt)+) => {{
+        let config = $crate::Config::new($crate::CompareMode::Strict);
+        $crate::assert_json_not_matches!($lhs, $rhs, &config, $($arg)+)
+    }};
+}
+
+/// Assert that a JSON value does not include another JSON value.
+///
+/// The values are compared like [`assert_json_include`](macro.assert_json_include.html) does,
+/// and the assertion fails if `actual` includes all of `expected`. Together with [`Matcher`]s
+/// this checks that fields were removed:
+///
+/// ```
+/// use serde_json_assert::{assert_json_not_include, Matcher};
+/// use serde_json::json;
+///
+/// assert_json_not_include!(
+///     actual: json!({ "user": { "name": "bob" } }),
+///     expected: json!({ "user": { "password": Matcher::Any } }),
+/// );
+/// ```
+#[macro_export]
+macro_rules! assert_json_not_include {
+    (actual: $actual:expr, expected: $expected:expr $(,)?) => {{
+        let config = $crate::Config::new($crate::CompareMode::Inclusive);
+        $crate::assert_json_not_matches!($actual, $expected, &config)
+    }};
+    (expected: $expected:expr, actual: $actual:expr $(,)?) => {{
+        $crate::assert_json_not_include!(actual: $actual, expected: $expected)
+    }};
+    (actual: $actual:expr, expected: $expected:expr, $($arg:tt)+) => {{
+        let config = $crate::Config::new($crate::CompareMode::Inclusive);
+        $crate::assert_json_not_matches!($actual, $expected, &config, $($arg)+)
+    }};
+    (expected: $expected:expr, actual: $actual:expr, $($arg:tt)+) => {{
+        $crate::assert_json_not_include!(actual: $actual, expected: $expected, $($arg)+)
+    }};
+}
+
+/// Assert that two JSON values don't match according to a configuration.
+///
+/// This is the negation of [`assert_json_matches`](macro.assert_json_matches.html): the
+/// assertion fails if comparing the values finds no differences.
+///
+/// ```
+/// use serde_json_assert::{assert_json_not_matches, CompareMode, Config};
+/// use serde_json::json;
+///
+/// let config = Config::new(CompareMode::Strict).ignore_path(".updated_at");
+///
+/// assert_json_not_matches!(
+///     json!({ "name": "rob", "updated_at": "2025-06-14" }),
+///     json!({ "name": "bob", "updated_at": "2025-06-13" }),
+///     &config,
+///     "The update didn't change anything but the timestamp",
+/// );
+/// ```
+#[macro_export]
+macro_rules! assert_json_not_matches {
+    ($lhs:expr, $rhs:expr, $config:expr $(,)?) => {{
+        if let Err(error) = $crate::__assert_json_not_matches_message(&$lhs, &$rhs, $config) {
+            panic!("\n{}", error);
+        }
+    }};
+    ($lhs:expr, $rhs:expr, $config:expr, $($arg:tt)+) => {{
+        if let Err(error) = $crate::__assert_json_not_matches_message(&$lhs, &$rhs, $config) {
+            panic!("\n{}\n\n{}", format_args!($($arg)+), error);
+        }
+    }};
+}
+
+/// Compare a value against a JSON snapshot file.
+///
+/// The snapshot file holds the expected value. Relative paths are resolved against the root of the
+/// crate that calls the macro. The comparison is strict unless a [`Config`] is given as the third
+/// argument.
+///
+/// Set the `SERDE_JSON_ASSERT_UPDATE` environment variable, for example to `1`, to create missing
+/// snapshots and rewrite the ones that don't match instead of failing:
+///
+/// ```text
+/// SERDE_JSON_ASSERT_UPDATE=1 cargo test
+/// ```
+///
+/// See [`try_assert_json_snapshot`] for how snapshots are compared and updated.
+///
+/// ```no_run
+/// use serde_json_assert::{assert_json_snapshot, CompareMode, Config};
+/// use serde_json::json;
+///
+/// let user = json!({ "id": 42, "name": "bob" });
+///
+/// assert_json_snapshot!(user, "tests/snapshots/user.json");
+///
+/// let config = Config::new(CompareMode::Inclusive);
+/// assert_json_snapshot!(user, "tests/snapshots/user_name.json", &config);
+/// ```
+#[macro_export]
+macro_rules! assert_json_snapshot {
+    ($value:expr, $path:expr $(,)?) => {{
+        let config = $crate::Config::new($crate::CompareMode::Strict);
+        $crate::assert_json_snapshot!($value, $path, &config)
+    }};
+    ($value:expr, $path:expr, $config:expr $(,)?) => {{
+        let path = ::std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join($path);
+        if let Err(error) = $crate::__assert_json_snapshot_message(&$value, &path, $config) {
+            panic!("\n{}", error);
+        }
+    }};
+    ($value:expr, $path:expr, $config:expr, $($arg:tt)+) => {{
+        let path = ::std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join($path);
+        if let Err(error) = $crate::__assert_json_snapshot_message(&$value, &path, $config) {
             panic!("\n{}\n\n{}", format_args!($($arg)+), error);
         }
     }};
@@ -333,6 +654,9 @@ macro_rules! assert_json_matches {
 /// Instead it returns a `Result` where the error is the message that would be passed to `panic!`.
 /// This is might be useful if you want to control how failures are reported and don't want to deal
 /// with panics.
+///
+/// If either value can't be converted to JSON the error describes that instead. Use
+/// [`try_assert_json_matches`] to tell the two cases apart.
 pub fn assert_json_matches_no_panic<Lhs, Rhs>(
     lhs: &Lhs,
     rhs: &Rhs,
@@ -342,37 +666,118 @@ where
     Lhs: Serialize,
     Rhs: Serialize,
 {
-    let lhs = serde_json::to_value(lhs).unwrap_or_else(|err| {
-        panic!(
-            "Couldn't convert left hand side value to JSON. Serde error: {}",
-            err
-        )
-    });
-    let rhs = serde_json::to_value(rhs).unwrap_or_else(|err| {
-        panic!(
-            "Couldn't convert right hand side value to JSON. Serde error: {}",
-            err
-        )
-    });
+    let (lhs, rhs) = to_values(lhs, rhs).map_err(|err| err.to_string())?;
+    compare(&lhs, &rhs, config, false)
+}
 
-    let diffs = diff(&lhs, &rhs, config);
+/// Compares two JSON values like [`assert_json_matches_no_panic`], but colors the message when
+/// the `color` feature is enabled and stderr is a terminal. Used by the assertion macros.
+#[doc(hidden)]
+pub fn __assert_json_matches_message<Lhs, Rhs>(
+    lhs: &Lhs,
+    rhs: &Rhs,
+    config: &Config,
+) -> Result<(), String>
+where
+    Lhs: Serialize,
+    Rhs: Serialize,
+{
+    let (lhs, rhs) = to_values(lhs, rhs).map_err(|err| err.to_string())?;
+    compare(&lhs, &rhs, config, color::enabled())
+}
+
+/// Compares two JSON values and returns the failure message of
+/// [`assert_json_not_matches`](macro.assert_json_not_matches.html) if they match. Used by the
+/// negative assertion macros.
+#[doc(hidden)]
+pub fn __assert_json_not_matches_message<Lhs, Rhs>(
+    lhs: &Lhs,
+    rhs: &Rhs,
+    config: &Config,
+) -> Result<(), String>
+where
+    Lhs: Serialize,
+    Rhs: Serialize,
+{
+    let (lhs, rhs) = to_values(lhs, rhs).map_err(|err| err.to_string())?;
+    if !matches(&lhs, &rhs, config) {
+        return Ok(());
+    }
+
+    let pretty = |value: &Value| serde_json::to_string_pretty(value).unwrap().indent(8);
+    let msg = match config.compare_mode {
+        CompareMode::Inclusive => format!(
+            "json value is included in actual:\n    expected:\n{}\n    actual:\n{}",
+            pretty(&rhs),
+            pretty(&lhs)
+        ),
+        CompareMode::Strict => format!(
+            "json values are equal:\n    lhs:\n{}\n    rhs:\n{}",
+            pretty(&lhs),
+            pretty(&rhs)
+        ),
+    };
+    Err(msg)
+}
+
+fn to_values<Lhs, Rhs>(lhs: &Lhs, rhs: &Rhs) -> Result<(Value, Value), Error>
+where
+    Lhs: Serialize,
+    Rhs: Serialize,
+{
+    let lhs = serde_json::to_value(lhs).map_err(Error::SerializeLhs)?;
+    let rhs = serde_json::to_value(rhs).map_err(Error::SerializeRhs)?;
+    Ok((lhs, rhs))
+}
+
+fn compare(lhs: &Value, rhs: &Value, config: &Config, color: bool) -> Result<(), String> {
+    let (diffs, skipped) = diff_counted(lhs, rhs, config);
 
     if diffs.is_empty() {
         Ok(())
     } else {
-        let msg = diffs
-            .into_iter()
-            .map(|d| d.to_string())
+        Err(render(lhs, rhs, config, &diffs, skipped, color))
+    }
+}
+
+/// Render the failure message for `diffs`, the differences between `lhs` and `rhs` found by
+/// [`diff_counted`] together with the number of `skipped` ones.
+fn render(
+    lhs: &Value,
+    rhs: &Value,
+    config: &Config,
+    diffs: &[DifferenceRef<'_>],
+    skipped: usize,
+    color: bool,
+) -> String {
+    if let OutputMode::Unified(context) = config.output_mode {
+        unified_diff(lhs, rhs, config, context, Painter { color })
+    } else {
+        // The precision of the format limits the length of the values in the message.
+        let mut msg = diffs
+            .iter()
+            .map(|d| match (color, config.max_value_len) {
+                (true, Some(len)) => format!("{:#.*}", len, d),
+                (true, None) => format!("{:#}", d),
+                (false, Some(len)) => format!("{:.*}", len, d),
+                (false, None) => d.to_string(),
+            })
             .collect::<Vec<_>>()
             .join("\n\n");
-        Err(msg)
+        match skipped {
+            0 => {}
+            1 => msg.push_str("\n\n… and 1 more difference"),
+            _ => msg.push_str(&format!("\n\n… and {} more differences", skipped)),
+        }
+        msg
     }
 }
 
 /// Compares two JSON values without panicking.
 ///
-/// Returns a `Result` containing either `Ok(())` if the values match,
-/// or an `Err` with a [`Vec<Difference>`](Difference) describing the differences.
+/// Returns a `Result` containing either `Ok(())` if the values match, or an [`Error`]. The error
+/// is either [`Error::Differences`] with a [`Vec<Difference>`](Difference) describing the
+/// differences, or tells which value could not be converted to JSON.
 ///
 /// # Note:
 ///
@@ -401,27 +806,12 @@ where
 /// let result = try_assert_json_matches(&lhs, &rhs, &config);
 /// assert!(result.is_err());
 /// ```
-pub fn try_assert_json_matches<Lhs, Rhs>(
-    lhs: &Lhs,
-    rhs: &Rhs,
-    config: &Config,
-) -> Result<(), Vec<Difference>>
+pub fn try_assert_json_matches<Lhs, Rhs>(lhs: &Lhs, rhs: &Rhs, config: &Config) -> Result<(), Error>
 where
     Lhs: Serialize,
     Rhs: Serialize,
 {
-    let lhs = serde_json::to_value(lhs).unwrap_or_else(|err| {
-        panic!(
-            "Couldn't convert left hand side value to JSON. Serde error: {}",
-            err
-        )
-    });
-    let rhs = serde_json::to_value(rhs).unwrap_or_else(|err| {
-        panic!(
-            "Couldn't convert right hand side value to JSON. Serde error: {}",
-            err
-        )
-    });
+    let (lhs, rhs) = to_values(lhs, rhs)?;
 
     let diffs = diff(&lhs, &rhs, config);
     let diffs_buf: Vec<Difference> = diffs.into_iter().map(|d| d.into()).collect();
@@ -429,7 +819,42 @@ where
     if diffs_buf.is_empty() {
         Ok(())
     } else {
-        Err(diffs_buf)
+        Err(Error::Differences(diffs_buf))
+    }
+}
+
+/// Returns `true` if two JSON values match according to a configuration.
+///
+/// The comparison stops at the first difference, so this is cheaper than
+/// [`try_assert_json_matches`] when the differences themselves aren't needed, for example to
+/// filter values. Values that can't be converted to JSON never match.
+///
+/// ```
+/// use serde_json_assert::{json_matches, CompareMode, Config, Matcher};
+/// use serde_json::json;
+///
+/// let config = Config::new(CompareMode::Inclusive);
+/// let users = vec![
+///     json!({ "id": 1, "name": "bob", "admin": true }),
+///     json!({ "id": 2, "name": "rob" }),
+///     json!({ "id": 3, "name": "alice", "admin": true }),
+/// ];
+///
+/// let admin = json!({ "id": Matcher::AnyNumber, "admin": true });
+/// let admins = users
+///     .iter()
+///     .filter(|user| json_matches(user, &admin, &config))
+///     .count();
+/// assert_eq!(admins, 2);
+/// ```
+pub fn json_matches<Lhs, Rhs>(lhs: &Lhs, rhs: &Rhs, config: &Config) -> bool
+where
+    Lhs: Serialize,
+    Rhs: Serialize,
+{
+    match to_values(lhs, rhs) {
+        Ok((lhs, rhs)) => matches(&lhs, &rhs, config),
+        Err(_) => false,
     }
 }
 
@@ -441,6 +866,12 @@ pub struct Config {
     pub(crate) compare_mode: CompareMode,
     pub(crate) numeric_mode: NumericMode,
     float_compare_mode: FloatCompareMode,
+    ignored_paths: Vec<PathPattern>,
+    path_configs: Vec<(PathPattern, Config)>,
+    redactions: Vec<(PathPattern, Matcher)>,
+    output_mode: OutputMode,
+    pub(crate) max_differences: Option<usize>,
+    max_value_len: Option<usize>,
 }
 
 impl Config {
@@ -453,6 +884,12 @@ impl Config {
             compare_mode,
             numeric_mode: NumericMode::Strict,
             float_compare_mode: FloatCompareMode::Exact,
+            ignored_paths: vec![],
+            path_configs: vec![],
+            redactions: vec![],
+            output_mode: OutputMode::Differences,
+            max_differences: None,
+            max_value_len: None,
         }
     }
 
@@ -490,6 +927,319 @@ impl Config {
         }
         self
     }
+
+    /// Compare arrays by aligning their elements instead of comparing them position by position.
+    ///
+    /// The elements are aligned along their longest common subsequence, so an element that was
+    /// inserted into or removed from an ordered array is reported on its own rather than as a
+    /// change to every element after it. Elements that were replaced are compared recursively
+    /// at their index in actual. In [`CompareMode::Inclusive`] actual may contain elements that
+    /// aren't in expected, as long as the expected elements appear in the same order.
+    ///
+    /// Aligning takes time and memory proportional to the product of the array lengths, so
+    /// arrays with more than about a million pairs of elements left after their common prefix
+    /// and suffix are compared position by position instead.
+    ///
+    /// ```should_panic
+    /// use serde_json_assert::{assert_json_matches, CompareMode, Config};
+    /// use serde_json::json;
+    ///
+    /// let config = Config::new(CompareMode::Strict).align_arrays(true);
+    ///
+    /// // Only reports that `3` is missing from lhs.
+    /// assert_json_matches!(json!([1, 2, 4, 5, 6]), json!([1, 2, 3, 4, 5, 6]), &config);
+    /// ```
+    ///
+    /// Passing `false` switches back to comparing elements by position if arrays were aligned,
+    /// and leaves other array comparisons, such as
+    /// [`consider_array_sorting(false)`](Config::consider_array_sorting), as they are.
+    pub fn align_arrays(mut self, align: bool) -> Self {
+        if align {
+            self.array_sorting_mode = ArraySortingMode::Align;
+        } else if self.array_sorting_mode == ArraySortingMode::Align {
+            self.array_sorting_mode = ArraySortingMode::Consider;
+        }
+        self
+    }
+
+    /// Compare arrays of objects by pairing up elements that have the same values for `keys`.
+    ///
+    /// Key values are compared like any other value, so matchers and the numeric mode apply to
+    /// them. Paired elements are compared with each other regardless of their position, and
+    /// differences inside them are reported with the key values in the path, such as
+    /// `.users[3:id=42].name`, where `3` is the index of the element in actual. Expected elements
+    /// without a match in actual are reported as missing. In [`CompareMode::Strict`] actual
+    /// elements without a match in expected are reported too. Elements that don't have all of
+    /// the keys are paired by their position among the other elements without the keys.
+    ///
+    /// This applies to every array compared with this config, so it is usually combined with
+    /// [`Config::path_config`].
+    ///
+    /// ```
+    /// use serde_json_assert::{assert_json_matches, CompareMode, Config};
+    /// use serde_json::json;
+    ///
+    /// let config = Config::new(CompareMode::Strict)
+    ///     .path_config(".users", |config| config.match_arrays_by_key(["id"]));
+    ///
+    /// assert_json_matches!(
+    ///     json!({ "users": [{ "id": 2, "name": "alice" }, { "id": 1, "name": "bob" }] }),
+    ///     json!({ "users": [{ "id": 1, "name": "bob" }, { "id": 2, "name": "alice" }] }),
+    ///     &config,
+    /// );
+    /// ```
+    pub fn match_arrays_by_key<I, K>(mut self, keys: I) -> Self
+    where
+        I: IntoIterator<Item = K>,
+        K: Into<String>,
+    {
+        self.array_sorting_mode = ArraySortingMode::Key(keys.into_iter().map(Into::into).collect());
+        self
+    }
+
+    /// Change how differences are presented in failure messages.
+    ///
+    /// The default `output_mode` is [`OutputMode::Differences`]. With [`OutputMode::Unified`] the
+    /// failure message is a unified diff of the two values instead, where lines starting with `-`
+    /// are from lhs (actual) and lines starting with `+` are from rhs (expected):
+    ///
+    /// ```
+    /// use serde_json_assert::{assert_json_matches_no_panic, CompareMode, Config, OutputMode};
+    /// use serde_json::json;
+    ///
+    /// let config = Config::new(CompareMode::Strict).output_mode(OutputMode::Unified(1));
+    ///
+    /// let result = assert_json_matches_no_panic(
+    ///     &json!({ "a": 1, "b": [true, false], "c": "x" }),
+    ///     &json!({ "a": 1, "b": [true, true], "c": "x" }),
+    ///     &config,
+    /// );
+    /// assert_eq!(
+    ///     result.unwrap_err(),
+    ///     r#"--- lhs
+    /// +++ rhs
+    /// @@ .b[1] @@
+    ///      true,
+    /// -    false
+    /// +    true
+    ///    ],"#
+    /// );
+    /// ```
+    pub fn output_mode(mut self, output_mode: OutputMode) -> Self {
+        self.output_mode = output_mode;
+        self
+    }
+
+    /// Report at most `max` differences.
+    ///
+    /// Once `max` differences are found the remaining ones are only counted, and failure messages
+    /// end with a summary such as `… and 12 more differences`. Counting them still compares the
+    /// values in full, but the differences that are left out aren't kept, and array elements
+    /// without a match aren't compared with the other elements to find their closest match. The
+    /// differences returned by [`try_assert_json_matches`] are limited as well. At least one
+    /// difference is always reported, and by default all of them are.
+    ///
+    /// ```
+    /// use serde_json_assert::{assert_json_matches_no_panic, CompareMode, Config};
+    /// use serde_json::json;
+    ///
+    /// let config = Config::new(CompareMode::Strict).max_differences(1);
+    ///
+    /// let result = assert_json_matches_no_panic(&json!([1, 2, 3]), &json!([4, 5, 6]), &config);
+    /// assert_eq!(
+    ///     result.unwrap_err(),
+    ///     r#"json atoms at path "[0]" are not equal:
+    ///     lhs:
+    ///         1
+    ///     rhs:
+    ///         4
+    ///
+    /// … and 2 more differences"#
+    /// );
+    /// ```
+    pub fn max_differences(mut self, max: usize) -> Self {
+        self.max_differences = Some(max.max(1));
+        self
+    }
+
+    /// Limit the values in failure messages to `max` characters.
+    ///
+    /// Values are pretty-printed, so a difference between large objects or arrays can print a
+    /// whole subtree. Longer values are cut off and end with `…`. By default values are printed
+    /// in full. This has no effect with [`OutputMode::Unified`].
+    ///
+    /// ```
+    /// use serde_json_assert::{assert_json_matches_no_panic, CompareMode, Config};
+    /// use serde_json::json;
+    ///
+    /// let config = Config::new(CompareMode::Strict).max_value_len(12);
+    ///
+    /// let result = assert_json_matches_no_panic(
+    ///     &json!({ "a": "a very long string" }),
+    ///     &json!({ "a": "short" }),
+    ///     &config,
+    /// );
+    /// assert_eq!(
+    ///     result.unwrap_err(),
+    ///     r#"json atoms at path ".a" are not equal:
+    ///     lhs:
+    ///         "a very long…
+    ///     rhs:
+    ///         "short""#
+    /// );
+    /// ```
+    pub fn max_value_len(mut self, max: usize) -> Self {
+        self.max_value_len = Some(max);
+        self
+    }
+
+    /// Ignore the values at `path` entirely.
+    ///
+    /// `path` is a [`PathPattern`], or a string that parses as one. Paths are written the way
+    /// they are printed in error messages, such as `.meta.request_id`, and may contain wildcards,
+    /// as in `.items[*].created_at` or `.**.updated_at`. Ignored values are never compared and
+    /// are not reported if missing from either side.
+    ///
+    /// ```
+    /// use serde_json_assert::{assert_json_matches, CompareMode, Config};
+    /// use serde_json::json;
+    ///
+    /// let config = Config::new(CompareMode::Strict)
+    ///     .ignore_path(".meta.request_id")
+    ///     .ignore_path(".items[*].created_at");
+    ///
+    /// assert_json_matches!(
+    ///     json!({
+    ///         "meta": { "request_id": "b5f1" },
+    ///         "items": [{ "id": 1, "created_at": "2025-06-13" }],
+    ///     }),
+    ///     json!({
+    ///         "meta": {},
+    ///         "items": [{ "id": 1 }],
+    ///     }),
+    ///     &config,
+    /// );
+    /// ```
+    ///
+    /// Panics if `path` is not a valid path.
+    pub fn ignore_path<P>(mut self, path: P) -> Self
+    where
+        P: TryInto<PathPattern>,
+        P::Error: fmt::Display,
+    {
+        self.ignored_paths.push(parse_pattern(path));
+        self
+    }
+
+    /// Use a different configuration for the values at `path` and everything below them.
+    ///
+    /// `configure` is called with a copy of this config, without any ignored paths, path configs
+    /// or redactions, and returns the config to use at `path`. If several path configs match a
+    /// value or its ancestors the one added last is used, so a broad pattern added after a
+    /// narrower one also overrides it below the narrower path.
+    ///
+    /// ```
+    /// use serde_json_assert::{assert_json_matches, CompareMode, Config, FloatCompareMode};
+    /// use serde_json::json;
+    ///
+    /// let config = Config::new(CompareMode::Strict)
+    ///     .path_config(".metrics", |config| {
+    ///         config.float_compare_mode(FloatCompareMode::Epsilon(1e-6))
+    ///     })
+    ///     .path_config(".tags", |config| config.consider_array_sorting(false));
+    ///
+    /// assert_json_matches!(
+    ///     json!({ "metrics": { "p99": 0.1500001 }, "tags": ["b", "a"] }),
+    ///     json!({ "metrics": { "p99": 0.15 }, "tags": ["a", "b"] }),
+    ///     &config,
+    /// );
+    /// ```
+    ///
+    /// Panics if `path` is not a valid path.
+    pub fn path_config<P, F>(mut self, path: P, configure: F) -> Self
+    where
+        P: TryInto<PathPattern>,
+        P::Error: fmt::Display,
+        F: FnOnce(Config) -> Config,
+    {
+        let base = Config {
+            ignored_paths: vec![],
+            path_configs: vec![],
+            redactions: vec![],
+            ..self.clone()
+        };
+        self.path_configs
+            .push((parse_pattern(path), configure(base)));
+        self
+    }
+
+    /// Redact the values at `path` in snapshots written by
+    /// [`assert_json_snapshot`](macro.assert_json_snapshot.html) and [`try_assert_json_snapshot`].
+    ///
+    /// Redacted values are written as the placeholder of `matcher`, so that volatile values such
+    /// as timestamps and tokens don't change the snapshot. When the snapshot is read back the
+    /// placeholder checks the value with `matcher` like any other [`Matcher`]. `path` can be a
+    /// [`Path`] or a [`PathPattern`] such as `.items[*].created_at`. If several redactions match
+    /// the same value the one added last is used.
+    ///
+    /// ```no_run
+    /// use serde_json_assert::{assert_json_snapshot, CompareMode, Config, Matcher};
+    /// use serde_json::json;
+    ///
+    /// let config = Config::new(CompareMode::Strict)
+    ///     .redact(".token", Matcher::AnyString)
+    ///     .redact(".items[*].created_at", Matcher::regex(r"^\d{4}-\d{2}-\d{2}$"));
+    ///
+    /// // Written as { "token": "{{any_string}}", "items": [{ "created_at": "{{regex:...}}" }] }
+    /// assert_json_snapshot!(
+    ///     json!({ "token": "b5f1", "items": [{ "created_at": "2025-06-13" }] }),
+    ///     "tests/snapshots/session.json",
+    ///     &config,
+    /// );
+    /// ```
+    ///
+    /// Redactions don't change how values are compared.
+    ///
+    /// Panics if `path` is not a valid path.
+    pub fn redact<P>(mut self, path: P, matcher: Matcher) -> Self
+    where
+        P: TryInto<PathPattern>,
+        P::Error: fmt::Display,
+    {
+        self.redactions.push((parse_pattern(path), matcher));
+        self
+    }
+
+    pub(crate) fn redaction(&self, path: &Path) -> Option<&Matcher> {
+        self.redactions
+            .iter()
+            .rev()
+            .find(|(pattern, _)| pattern.matches(path))
+            .map(|(_, matcher)| matcher)
+    }
+
+    /// Returns the config of the last path config added for `path` or one of its ancestors.
+    pub(crate) fn config_at(&self, path: &PathRef<'_>) -> Option<&Config> {
+        self.path_configs
+            .iter()
+            .rev()
+            .find(|(pattern, _)| pattern.matches_ancestor_ref(path))
+            .map(|(_, config)| config)
+    }
+
+    pub(crate) fn is_ignored(&self, path: &PathRef<'_>) -> bool {
+        self.ignored_paths
+            .iter()
+            .any(|pattern| pattern.matches_ref(path))
+    }
+}
+
+fn parse_pattern<P>(path: P) -> PathPattern
+where
+    P: TryInto<PathPattern>,
+    P::Error: fmt::Display,
+{
+    path.try_into().unwrap_or_else(|err| panic!("{}", err))
 }
 
 /// Mode for how JSON values should be compared.
@@ -507,12 +1257,16 @@ pub enum CompareMode {
 }
 
 /// Should array sorting be taken in consideration
-#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 enum ArraySortingMode {
     ///consider
     Consider,
     /// ignore
     Ignore,
+    /// ignore, and pair elements by the values of these keys
+    Key(Vec<String>),
+    /// consider, and align elements along their longest common subsequence
+    Align,
 }
 
 /// How should numbers be compared.
@@ -535,6 +1289,16 @@ pub enum FloatCompareMode {
 
 impl Eq for FloatCompareMode {}
 
+/// How differences are presented in failure messages.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum OutputMode {
+    /// Every difference is described on its own, together with the path to it.
+    Differences,
+    /// A unified diff of the two pretty-printed values, with this many unchanged lines of context
+    /// around each change.
+    Unified(usize),
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -781,6 +1545,90 @@ mod tests {
         );
     }
 
+    #[test]
+    fn matcher_output_message() {
+        let result = test_partial_match(json!({ "a": "1" }), json!({ "a": Matcher::AnyNumber }));
+        assert_output_eq(
+            result,
+            Err(r#"json atom at path ".a" does not match:
+    expected:
+        any number
+    actual:
+        "1""#),
+        );
+
+        let result = test_partial_match(
+            json!({ "a": "v1.0" }),
+            json!({ "a": Matcher::regex(r"^\d+\.\d+$") }),
+        );
+        assert_output_eq(
+            result,
+            Err(r#"json atom at path ".a" does not match:
+    expected:
+        any string matching /^\d+\.\d+$/
+    actual:
+        "v1.0""#),
+        );
+
+        let result = test_exact_match(json!({ "a": null }), json!({ "a": Matcher::NotNull }));
+        assert_output_eq(
+            result,
+            Err(r#"json atom at path ".a" does not match:
+    rhs:
+        any non-null value
+    lhs:
+        null"#),
+        );
+    }
+
+    #[test]
+    fn keyed_array_output_message() {
+        let config = Config::new(CompareMode::Inclusive).match_arrays_by_key(["id"]);
+        let result = assert_json_matches_no_panic(
+            &json!([{ "id": 1, "name": "bob" }, { "id": "x", "name": "eve" }]),
+            &json!([{ "id": "x", "name": "alice" }, { "id": 3 }]),
+            &config,
+        );
+        assert_output_eq(
+            result,
+            Err(r#"json atoms at path "[1:id="x"].name" are not equal:
+    expected:
+        "alice"
+    actual:
+        "eve"
+
+json atom at path "[1:id=3]" is missing from actual"#),
+        );
+    }
+
+    #[test]
+    fn unordered_array_output_message() {
+        let config = Config::new(CompareMode::Strict).consider_array_sorting(false);
+        let result = assert_json_matches_no_panic(
+            &json!([{ "id": 1 }, { "id": 2, "name": "bob" }, 3]),
+            &json!([{ "id": 2, "name": "alice" }, { "id": 1 }]),
+            &config,
+        );
+        assert_output_eq(
+            result,
+            Err(r#"json atom at path "[0]" has no match in lhs:
+    rhs:
+        {
+          "id": 2,
+          "name": "alice"
+        }
+    closest lhs at path "[1]":
+        {
+          "id": 2,
+          "name": "bob"
+        }
+
+json atom at path "[2]" has no match in rhs:
+    lhs:
+        3"#),
+        );
+    }
+
     #[test]
     fn exact_match_output_message() {
         let result = test_exact_match(json!({ "a": { "b": 1 } }), json!({ "a": {} }));
@@ -796,6 +1644,71 @@ mod tests {
         );
     }
 
+    #[test]
+    fn not_matches_output_message() {
+        let strict = Config::new(CompareMode::Strict);
+        let result =
+            __assert_json_not_matches_message(&json!({ "a": 1 }), &json!({ "a": 2 }), &strict);
+        assert_output_eq(result, Ok(()));
+
+        let result = __assert_json_not_matches_message(&json!([1]), &json!([1]), &strict);
+        assert_output_eq(
+            result,
+            Err(r#"json values are equal:
+    lhs:
+        [
+          1
+        ]
+    rhs:
+        [
+          1
+        ]"#),
+        );
+
+        let inclusive = Config::new(CompareMode::Inclusive);
+        let result = __assert_json_not_matches_message(
+            &json!({ "a": 1, "b": 2 }),
+            &json!({ "a": Matcher::AnyNumber }),
+            &inclusive,
+        );
+        assert_output_eq(
+            result,
+            Err(r#"json value is included in actual:
+    expected:
+        {
+          "a": "{{any_number}}"
+        }
+    actual:
+        {
+          "a": 1,
+          "b": 2
+        }"#),
+        );
+    }
+
+    #[test]
+    fn limited_output_message() {
+        let config = Config::new(CompareMode::Inclusive)
+            .max_differences(1)
+            .max_value_len(8);
+        let result = assert_json_matches_no_panic(
+            &json!({ "a": { "b": [1, 2, 3] }, "c": 1 }),
+            &json!({ "a": "abcdefghijk", "c": 2 }),
+            &config,
+        );
+        assert_output_eq(
+            result,
+            Err(r#"json atoms at path ".a" are not equal:
+    expected:
+        "abcdefg…
+    actual:
+        {
+          "b":…
+
+… and 1 more difference"#),
+        );
+    }
+
     fn assert_output_eq(actual: Result<(), String>, expected: Result<(), &str>) {
         match (actual, expected) {
             (Ok(()), Ok(())) => {}
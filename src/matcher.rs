@@ -0,0 +1,365 @@
+use regex::Regex;
+use serde::{Serialize, Serializer};
+use serde_json::Value;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fmt;
+
+const PREFIX: &str = "{{";
+const SUFFIX: &str = "}}";
+const REGEX_PREFIX: &str = "regex:";
+/// The number of compiled patterns kept per thread before the cache is cleared.
+const REGEX_CACHE_SIZE: usize = 64;
+
+thread_local! {
+    static REGEX_CACHE: RefCell<HashMap<String, Result<Regex, String>>> =
+        RefCell::new(HashMap::new());
+}
+
+/// A placeholder that matches JSON values by type rather than by value.
+///
+/// Matchers are meant to be placed in the *expected* value. They serialize to a reserved string,
+/// such as `"{{any_string}}"`, so they can be used anywhere a value is accepted, for example
+/// inside [`serde_json::json!`]. When a reserved string is found on the expected side of a
+/// comparison the actual value is only checked against the matcher.
+///
+/// To expect a string that looks like a matcher, such as `"{{any}}"`, prefix it with another `{{`
+/// or use [`Matcher::escape`]: the expected `"{{{{any}}"` only matches the actual string
+/// `"{{any}}"`.
+///
+/// ```
+/// use serde_json_assert::{assert_json_include, Matcher};
+/// use serde_json::json;
+///
+/// assert_json_include!(
+///     actual: json!({ "id": 42, "name": "bob", "created_at": "2025-06-13" }),
+///     expected: json!({ "id": Matcher::AnyNumber, "created_at": Matcher::AnyString }),
+/// );
+/// ```
+///
+/// Strings can be matched against a regular expression with [`Matcher::regex`]:
+///
+/// ```
+/// use serde_json_assert::{assert_json_include, Matcher};
+/// use serde_json::json;
+///
+/// assert_json_include!(
+///     actual: json!({ "version": "1.2.3" }),
+///     expected: json!({ "version": Matcher::regex(r"^\d+\.\d+\.\d+$") }),
+/// );
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Matcher {
+    /// Matches any value, including `null`. Serialized as `"{{any}}"`.
+    Any,
+    /// Matches any value except `null`. Serialized as `"{{not_null}}"`.
+    NotNull,
+    /// Matches any boolean. Serialized as `"{{any_bool}}"`.
+    AnyBool,
+    /// Matches any number. Serialized as `"{{any_number}}"`.
+    AnyNumber,
+    /// Matches any string. Serialized as `"{{any_string}}"`.
+    AnyString,
+    /// Matches any array. Serialized as `"{{any_array}}"`.
+    AnyArray,
+    /// Matches any object. Serialized as `"{{any_object}}"`.
+    AnyObject,
+    /// Matches any string that matches the regular expression. Serialized as
+    /// `"{{regex:<pattern>}}"`.
+    ///
+    /// The pattern is searched for anywhere in the string, so use `^` and `$` to anchor it. A
+    /// pattern that is not a valid regular expression matches nothing.
+    Regex(RegexPattern),
+}
+
+impl Matcher {
+    /// Create a [`Matcher::Regex`] from a regular expression.
+    ///
+    /// Panics if `pattern` is not a valid regular expression.
+    pub fn regex(pattern: impl Into<String>) -> Self {
+        let pattern = RegexPattern::new(pattern);
+        if let Err(err) = &pattern.regex {
+            panic!("invalid regex pattern {:?}: {}", pattern.pattern, err);
+        }
+        Matcher::Regex(pattern)
+    }
+
+    /// Returns `true` if `value` is accepted by this matcher.
+    pub fn matches(&self, value: &Value) -> bool {
+        match self {
+            Matcher::Any => true,
+            Matcher::NotNull => !value.is_null(),
+            Matcher::AnyBool => value.is_boolean(),
+            Matcher::AnyNumber => value.is_number(),
+            Matcher::AnyString => value.is_string(),
+            Matcher::AnyArray => value.is_array(),
+            Matcher::AnyObject => value.is_object(),
+            Matcher::Regex(pattern) => match (value.as_str(), &pattern.regex) {
+                (Some(string), Ok(regex)) => regex.is_match(string),
+                _ => false,
+            },
+        }
+    }
+
+    /// Escape `literal` so that it is compared as a plain string when used in an expected value,
+    /// even if it looks like a matcher.
+    ///
+    /// ```
+    /// use serde_json_assert::{assert_json_eq, Matcher};
+    /// use serde_json::json;
+    ///
+    /// assert_eq!(Matcher::escape("{{any}}"), "{{{{any}}");
+    /// assert_eq!(Matcher::escape("plain"), "plain");
+    ///
+    /// assert_json_eq!(
+    ///     json!({ "template": "{{any}}" }),
+    ///     json!({ "template": Matcher::escape("{{any}}") }),
+    /// );
+    /// ```
+    pub fn escape(literal: &str) -> String {
+        if Self::parse(literal).is_some() || is_escaped(literal) {
+            format!("{}{}", PREFIX, literal)
+        } else {
+            literal.to_owned()
+        }
+    }
+
+    /// Parse a matcher from its serialized form, if `value` is one.
+    pub(crate) fn from_value(value: &Value) -> Option<Matcher> {
+        Self::parse(value.as_str()?)
+    }
+
+    fn parse(token: &str) -> Option<Matcher> {
+        let name = token.strip_prefix(PREFIX)?.strip_suffix(SUFFIX)?;
+
+        match name {
+            "any" => Some(Matcher::Any),
+            "not_null" => Some(Matcher::NotNull),
+            "any_bool" => Some(Matcher::AnyBool),
+            "any_number" => Some(Matcher::AnyNumber),
+            "any_string" => Some(Matcher::AnyString),
+            "any_array" => Some(Matcher::AnyArray),
+            "any_object" => Some(Matcher::AnyObject),
+            _ => name
+                .strip_prefix(REGEX_PREFIX)
+                .map(|pattern| Matcher::Regex(RegexPattern::new(pattern))),
+        }
+    }
+
+    /// The reserved string this matcher serializes to.
+    fn token(&self) -> String {
+        match self {
+            Matcher::Regex(pattern) => {
+                format!("{}{}{}{}", PREFIX, REGEX_PREFIX, pattern.as_str(), SUFFIX)
+            }
+            _ => format!("{}{}{}", PREFIX, self.name(), SUFFIX),
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        match self {
+            Matcher::Any => "any",
+            Matcher::NotNull => "not_null",
+            Matcher::AnyBool => "any_bool",
+            Matcher::AnyNumber => "any_number",
+            Matcher::AnyString => "any_string",
+            Matcher::AnyArray => "any_array",
+            Matcher::AnyObject => "any_object",
+            Matcher::Regex(_) => "regex",
+        }
+    }
+}
+
+/// The regular expression of a [`Matcher::Regex`].
+///
+/// The pattern is compiled once, when the matcher is created. Patterns are compared by their
+/// source text.
+#[derive(Debug, Clone)]
+pub struct RegexPattern {
+    pattern: String,
+    regex: Result<Regex, String>,
+}
+
+impl RegexPattern {
+    fn new(pattern: impl Into<String>) -> Self {
+        let pattern = pattern.into();
+        let regex = REGEX_CACHE.with(|cache| {
+            let mut cache = cache.borrow_mut();
+            if let Some(regex) = cache.get(&pattern) {
+                return regex.clone();
+            }
+            if cache.len() >= REGEX_CACHE_SIZE {
+                cache.clear();
+            }
+            let regex = Regex::new(&pattern).map_err(|err| err.to_string());
+            cache.insert(pattern.clone(), regex.clone());
+            regex
+        });
+        Self { pattern, regex }
+    }
+
+    /// Returns the source text of the regular expression.
+    pub fn as_str(&self) -> &str {
+        &self.pattern
+    }
+
+    /// Returns `true` if the pattern is a valid regular expression.
+    pub fn is_valid(&self) -> bool {
+        self.regex.is_ok()
+    }
+}
+
+impl PartialEq for RegexPattern {
+    fn eq(&self, other: &Self) -> bool {
+        self.pattern == other.pattern
+    }
+}
+
+impl Eq for RegexPattern {}
+
+impl fmt::Display for RegexPattern {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.pattern)
+    }
+}
+
+/// Returns the literal string that `value` stands for if it is an escaped matcher, see
+/// [`Matcher::escape`].
+pub(crate) fn unescape(value: &Value) -> Option<&str> {
+    value
+        .as_str()
+        .filter(|value| is_escaped(value))?
+        .strip_prefix(PREFIX)
+}
+
+fn is_escaped(value: &str) -> bool {
+    value
+        .strip_prefix(PREFIX)
+        .is_some_and(|rest| Matcher::parse(rest).is_some() || is_escaped(rest))
+}
+
+impl Serialize for Matcher {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(&self.token())
+    }
+}
+
+impl From<Matcher> for Value {
+    fn from(matcher: Matcher) -> Self {
+        Value::String(matcher.token())
+    }
+}
+
+impl fmt::Display for Matcher {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Matcher::Any => write!(f, "any value"),
+            Matcher::NotNull => write!(f, "any non-null value"),
+            Matcher::AnyBool => write!(f, "any boolean"),
+            Matcher::AnyNumber => write!(f, "any number"),
+            Matcher::AnyString => write!(f, "any string"),
+            Matcher::AnyArray => write!(f, "any array"),
+            Matcher::AnyObject => write!(f, "any object"),
+            Matcher::Regex(pattern) => match &pattern.regex {
+                Ok(_) => write!(f, "any string matching /{}/", pattern),
+                Err(err) => write!(f, "a valid regex, but /{}/ is invalid: {}", pattern, err),
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_round_trip() {
+        let matchers = [
+            Matcher::Any,
+            Matcher::NotNull,
+            Matcher::AnyBool,
+            Matcher::AnyNumber,
+            Matcher::AnyString,
+            Matcher::AnyArray,
+            Matcher::AnyObject,
+            Matcher::regex(r"^\d{3}$"),
+            Matcher::regex("}}"),
+        ];
+        for matcher in matchers {
+            let value = serde_json::to_value(&matcher).unwrap();
+            assert_eq!(value, Value::from(matcher.clone()));
+            assert_eq!(Matcher::from_value(&value), Some(matcher));
+        }
+
+        assert_eq!(json!(Matcher::AnyString), json!("{{any_string}}"));
+        assert_eq!(json!(Matcher::regex("^a+$")), json!("{{regex:^a+$}}"));
+        assert_eq!(Matcher::from_value(&json!("{{unknown}}")), None);
+        assert_eq!(Matcher::from_value(&json!("any_string")), None);
+        assert_eq!(Matcher::from_value(&json!(1)), None);
+    }
+
+    #[test]
+    fn test_matches() {
+        assert!(Matcher::Any.matches(&json!(null)));
+        assert!(!Matcher::NotNull.matches(&json!(null)));
+        assert!(Matcher::NotNull.matches(&json!(0)));
+        assert!(Matcher::AnyBool.matches(&json!(false)));
+        assert!(Matcher::AnyNumber.matches(&json!(1.5)));
+        assert!(!Matcher::AnyNumber.matches(&json!("1.5")));
+        assert!(Matcher::AnyString.matches(&json!("")));
+        assert!(Matcher::AnyArray.matches(&json!([])));
+        assert!(Matcher::AnyObject.matches(&json!({})));
+        assert!(!Matcher::AnyObject.matches(&json!([])));
+    }
+
+    #[test]
+    fn test_regex() {
+        let matcher = Matcher::regex(r"^\d{4}-\d{2}-\d{2}$");
+        assert!(matcher.matches(&json!("2025-06-13")));
+        assert!(!matcher.matches(&json!("2025-6-13")));
+        assert!(!matcher.matches(&json!(20250613)));
+        assert!(Matcher::regex("b").matches(&json!("abc")));
+
+        // Invalid patterns read back from JSON match nothing and say why.
+        let invalid = Matcher::from_value(&json!("{{regex:(}}")).unwrap();
+        assert!(!invalid.matches(&json!("(")));
+        let Matcher::Regex(pattern) = &invalid else {
+            panic!("unexpected matcher {:?}", invalid);
+        };
+        assert!(!pattern.is_valid());
+        assert_eq!(pattern.as_str(), "(");
+        assert!(invalid
+            .to_string()
+            .starts_with("a valid regex, but /(/ is invalid: "));
+        assert_eq!(Value::from(invalid), json!("{{regex:(}}"));
+
+        assert_eq!(
+            matcher.to_string(),
+            r"any string matching /^\d{4}-\d{2}-\d{2}$/"
+        );
+    }
+
+    #[test]
+    fn test_escape() {
+        assert_eq!(Matcher::escape("{{any}}"), "{{{{any}}");
+        assert_eq!(Matcher::escape("{{{{any}}"), "{{{{{{any}}");
+        assert_eq!(Matcher::escape("{{unknown}}"), "{{unknown}}");
+        assert_eq!(Matcher::escape("{{"), "{{");
+
+        assert_eq!(unescape(&json!("{{{{any}}")), Some("{{any}}"));
+        assert_eq!(unescape(&json!("{{{{{{any}}")), Some("{{{{any}}"));
+        assert_eq!(unescape(&json!("{{any}}")), None);
+        assert_eq!(unescape(&json!("{{{{unknown}}")), None);
+        assert_eq!(Matcher::from_value(&json!("{{{{any}}")), None);
+    }
+
+    #[test]
+    #[should_panic(expected = "invalid regex pattern")]
+    fn test_invalid_regex() {
+        Matcher::regex("(");
+    }
+}
@@ -0,0 +1,155 @@
+use std::collections::VecDeque;
+
+/// Find a maximum matching in a bipartite graph using the Hopcroft–Karp algorithm.
+///
+/// `edges[u]` lists the nodes on the right side that the node `u` on the left side may be paired
+/// with, and `right_len` is the number of nodes on the right side. Returns the node on the right
+/// side paired with each node on the left side, if any.
+pub(crate) fn max_matching(edges: &[Vec<usize>], right_len: usize) -> Vec<Option<usize>> {
+    let mut matching = Matching {
+        edges,
+        left: vec![None; edges.len()],
+        right: vec![None; right_len],
+        layers: vec![0; edges.len()],
+    };
+    while matching.build_layers() {
+        for u in 0..edges.len() {
+            if matching.left[u].is_none() {
+                matching.augment(u);
+            }
+        }
+    }
+    matching.left
+}
+
+const UNREACHABLE: usize = usize::MAX;
+
+struct Matching<'a> {
+    edges: &'a [Vec<usize>],
+    /// The right node paired with each left node.
+    left: Vec<Option<usize>>,
+    /// The left node paired with each right node.
+    right: Vec<Option<usize>>,
+    /// The distance of each left node from a free left node, along alternating paths.
+    layers: Vec<usize>,
+}
+
+impl Matching<'_> {
+    /// Layer the left nodes by breadth-first search from the free ones. Returns `true` if an
+    /// augmenting path exists.
+    fn build_layers(&mut self) -> bool {
+        let mut queue = VecDeque::new();
+        for u in 0..self.edges.len() {
+            if self.left[u].is_none() {
+                self.layers[u] = 0;
+                queue.push_back(u);
+            } else {
+                self.layers[u] = UNREACHABLE;
+            }
+        }
+
+        let mut found = false;
+        while let Some(u) = queue.pop_front() {
+            for &v in &self.edges[u] {
+                match self.right[v] {
+                    None => found = true,
+                    Some(w) if self.layers[w] == UNREACHABLE => {
+                        self.layers[w] = self.layers[u] + 1;
+                        queue.push_back(w);
+                    }
+                    Some(_) => {}
+                }
+            }
+        }
+        found
+    }
+
+    /// Look for an augmenting path from `root` along the layers, and flip it if one is found.
+    ///
+    /// The search is depth-first, with an explicit stack so that long paths can't overflow the
+    /// call stack.
+    fn augment(&mut self, root: usize) -> bool {
+        // The left nodes on the current path, each with the number of its edges tried so far.
+        let mut stack = vec![(root, 0)];
+        while let Some(&(u, tried)) = stack.last() {
+            let Some(&v) = self.edges[u].get(tried) else {
+                // No augmenting path goes through `u` in this phase.
+                self.layers[u] = UNREACHABLE;
+                stack.pop();
+                continue;
+            };
+            stack.last_mut().unwrap().1 += 1;
+            match self.right[v] {
+                None => {
+                    // Every node on the path takes the right node of the edge it last tried.
+                    for &(u, tried) in &stack {
+                        let v = self.edges[u][tried - 1];
+                        self.left[u] = Some(v);
+                        self.right[v] = Some(u);
+                    }
+                    return true;
+                }
+                Some(w) if self.layers[w] == self.layers[u] + 1 => stack.push((w, 0)),
+                Some(_) => {}
+            }
+        }
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn size(matching: &[Option<usize>]) -> usize {
+        matching.iter().flatten().count()
+    }
+
+    #[test]
+    fn test_max_matching() {
+        assert_eq!(max_matching(&[], 0), vec![]);
+        assert_eq!(max_matching(&[vec![]], 1), vec![None]);
+        assert_eq!(max_matching(&[vec![0]], 1), vec![Some(0)]);
+
+        // A greedy pairing would give 0 to the first node and leave the second one unpaired.
+        assert_eq!(
+            max_matching(&[vec![0, 1], vec![0]], 2),
+            vec![Some(1), Some(0)]
+        );
+
+        // Only one of the left nodes can be paired.
+        assert_eq!(size(&max_matching(&[vec![0], vec![0], vec![0]], 1)), 1);
+
+        let edges = [vec![0, 1], vec![0, 2], vec![1], vec![2, 3], vec![3]];
+        let matching = max_matching(&edges, 4);
+        assert_eq!(size(&matching), 4);
+        let mut paired = matching.iter().flatten().copied().collect::<Vec<_>>();
+        paired.sort_unstable();
+        paired.dedup();
+        assert_eq!(paired.len(), 4);
+    }
+
+    #[test]
+    fn test_large_matching() {
+        // Every left node may be paired with itself or the next right node, and the greedy
+        // choice of the next one is always wrong for the last node.
+        let n = 2000;
+        let edges = (0..n)
+            .map(|u| if u + 1 < n { vec![u + 1, u] } else { vec![0] })
+            .collect::<Vec<_>>();
+        assert_eq!(size(&max_matching(&edges, n)), n);
+    }
+
+    #[test]
+    fn test_long_augmenting_path() {
+        // Every left node but the last one takes the next right node first, so the last one can
+        // only be paired along a path through all of the others.
+        let n = 200_000;
+        let edges = (0..n)
+            .map(|u| if u + 1 < n { vec![u + 1, u] } else { vec![u] })
+            .collect::<Vec<_>>();
+        let matching = max_matching(&edges, n);
+        assert_eq!(size(&matching), n);
+        assert_eq!(matching[0], Some(0));
+    }
+}
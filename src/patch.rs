@@ -0,0 +1,888 @@
+use crate::diff::diff;
+use crate::matcher::{self, Matcher};
+use crate::{Config, Difference, Error, Key, Path};
+use serde::Serialize;
+use serde_json::{json, Map, Value};
+use std::{error, fmt};
+
+/// Build an [RFC 6902] JSON Patch that turns actual (lhs) into expected (rhs).
+///
+/// `differences` are the differences returned by [`try_assert_json_matches`]. Each difference
+/// becomes an `add`, `remove` or `replace` operation. The operations are ordered so they can be
+/// applied one after the other: replacements come first, then removals with array elements
+/// removed from the back, then additions.
+///
+/// Differences reported by a [`Matcher`], and expected values with a matcher inside them, are
+/// skipped, since there is no single value that would fix them. Escaped matchers are written as
+/// the strings they stand for. Array elements that are missing from actual but have no position of
+/// their own, such as unmatched elements of arrays compared without ordering, are appended with the
+/// `-` index.
+///
+/// ```
+/// use serde_json_assert::{json_patch, try_assert_json_matches, CompareMode, Config};
+/// use serde_json::json;
+///
+/// let actual = json!({ "name": "bob", "tags": ["a", "b", "c"], "old": true });
+/// let expected = json!({ "name": "alice", "tags": ["a"], "new": 1 });
+///
+/// let err =
+///     try_assert_json_matches(&actual, &expected, &Config::new(CompareMode::Strict)).unwrap_err();
+///
+/// assert_eq!(
+///     json_patch(err.differences()),
+///     json!([
+///         { "op": "replace", "path": "/name", "value": "alice" },
+///         { "op": "remove", "path": "/tags/2" },
+///         { "op": "remove", "path": "/tags/1" },
+///         { "op": "remove", "path": "/old" },
+///         { "op": "add", "path": "/new", "value": 1 },
+///     ])
+/// );
+/// ```
+///
+/// [RFC 6902]: https://www.rfc-editor.org/rfc/rfc6902
+/// [`try_assert_json_matches`]: crate::try_assert_json_matches
+/// [`Matcher`]: crate::Matcher
+pub fn json_patch(differences: &[Difference]) -> Value {
+    let mut replaces = vec![];
+    let mut removes = vec![];
+    let mut adds = vec![];
+    let mut appends = vec![];
+
+    for difference in differences {
+        let expected = match difference.expected_value().map(literal) {
+            Some(None) => continue,
+            Some(Some(expected)) => Some(expected),
+            None => None,
+        };
+        match (difference.actual(), expected) {
+            (Some(_), Some(rhs)) => {
+                let path = difference.closest_match().unwrap_or(difference.path());
+                replaces.push(json!({
+                    "op": "replace",
+                    "path": path.to_json_pointer(),
+                    "value": rhs,
+                }));
+            }
+            (None, Some(rhs)) if difference.is_unmatched() || is_keyed(difference.path()) => {
+                appends.push(json!({
+                    "op": "add",
+                    "path": format!("{}/-", parent(difference.path()).to_json_pointer()),
+                    "value": rhs,
+                }));
+            }
+            (None, Some(rhs)) => adds.push((difference.path(), rhs)),
+            (Some(_), None) => removes.push(difference.path()),
+            (None, None) => {}
+        }
+    }
+
+    // Removing array elements from the back keeps the indexes of the remaining removals valid,
+    // and adding them from the front puts each one at its final index.
+    removes.sort_by_key(|path| std::cmp::Reverse(sort_key(path)));
+    adds.sort_by_key(|(path, _)| sort_key(path));
+
+    let removes = removes.into_iter().map(|path| {
+        json!({
+            "op": "remove",
+            "path": path.to_json_pointer(),
+        })
+    });
+    let adds = adds.into_iter().map(|(path, value)| {
+        json!({
+            "op": "add",
+            "path": path.to_json_pointer(),
+            "value": value,
+        })
+    });
+
+    Value::Array(
+        replaces
+            .into_iter()
+            .chain(removes)
+            .chain(adds)
+            .chain(appends)
+            .collect(),
+    )
+}
+
+/// Returns `value` with escaped matchers replaced by the strings they stand for, or `None` if it
+/// contains a matcher.
+fn literal(value: &Value) -> Option<Value> {
+    if let Some(literal) = matcher::unescape(value) {
+        return Some(Value::from(literal));
+    }
+    if Matcher::from_value(value).is_some() {
+        return None;
+    }
+    match value {
+        Value::Array(values) => values
+            .iter()
+            .map(literal)
+            .collect::<Option<_>>()
+            .map(Value::Array),
+        Value::Object(map) => map
+            .iter()
+            .map(|(key, value)| Some((key.clone(), literal(value)?)))
+            .collect::<Option<Map<_, _>>>()
+            .map(Value::Object),
+        _ => Some(value.clone()),
+    }
+}
+
+/// Build an [RFC 7386] JSON Merge Patch that turns `actual` into expected.
+///
+/// `differences` are the differences found when comparing `actual` with expected, as returned by
+/// [`try_assert_json_matches`]. The merge patch only contains the members of objects that
+/// changed, with `null` for members that should be removed. Arrays can't be merged, so an array
+/// with any difference in it is replaced as a whole.
+///
+/// Merge patches can't set a value to `null`, so a value that is `null` in expected shows up as
+/// removed. Differences reported by a [`Matcher`] are skipped, like in [`json_patch`].
+///
+/// Without any differences the patch is `{}` if `actual` is an object, and `actual` itself
+/// otherwise, so that applying it never changes `actual`.
+///
+/// Returns a [`PatchError`] if `differences` weren't found by comparing `actual`, so that they
+/// don't apply to it.
+///
+/// ```
+/// use serde_json_assert::{json_merge_patch, try_assert_json_matches, CompareMode, Config};
+/// use serde_json::json;
+///
+/// let actual = json!({ "user": { "name": "bob", "age": 42 }, "tags": ["a", "b"] });
+/// let expected = json!({ "user": { "name": "alice" }, "tags": ["a"] });
+///
+/// let err =
+///     try_assert_json_matches(&actual, &expected, &Config::new(CompareMode::Strict)).unwrap_err();
+///
+/// assert_eq!(
+///     json_merge_patch(&actual, err.differences()),
+///     Ok(json!({ "user": { "name": "alice", "age": null }, "tags": ["a"] }))
+/// );
+/// ```
+///
+/// [RFC 7386]: https://www.rfc-editor.org/rfc/rfc7386
+/// [`try_assert_json_matches`]: crate::try_assert_json_matches
+/// [`Matcher`]: crate::Matcher
+pub fn json_merge_patch(actual: &Value, differences: &[Difference]) -> Result<Value, PatchError> {
+    let mut expected = actual.clone();
+    apply_json_patch(&mut expected, &json_patch(differences))?;
+    // An empty object leaves objects as they are, but replaces any other value.
+    Ok(
+        merge_diff(actual, &expected).unwrap_or_else(|| match actual {
+            Value::Object(_) => json!({}),
+            _ => actual.clone(),
+        }),
+    )
+}
+
+/// Returns the merge patch that turns `from` into `to`, or `None` if they are equal.
+fn merge_diff(from: &Value, to: &Value) -> Option<Value> {
+    match (from, to) {
+        (Value::Object(from), Value::Object(to)) => {
+            let mut patch = Map::new();
+            for (key, from) in from {
+                match to.get(key) {
+                    Some(to) => {
+                        if let Some(value) = merge_diff(from, to) {
+                            patch.insert(key.clone(), value);
+                        }
+                    }
+                    None => {
+                        patch.insert(key.clone(), Value::Null);
+                    }
+                }
+            }
+            for (key, to) in to {
+                if !from.contains_key(key) {
+                    patch.insert(key.clone(), to.clone());
+                }
+            }
+            (!patch.is_empty()).then_some(Value::Object(patch))
+        }
+        _ if from == to => None,
+        _ => Some(to.clone()),
+    }
+}
+
+/// Apply an [RFC 6902] JSON Patch to `value`.
+///
+/// All six operations are supported: `add`, `remove`, `replace`, `move`, `copy` and `test`. The
+/// patch is applied as a whole, so `value` is left untouched if any operation fails. Like the RFC
+/// requires, `test` compares numbers by their value, so `1` and `1.0` are equal.
+///
+/// ```
+/// use serde_json_assert::apply_json_patch;
+/// use serde_json::json;
+///
+/// let mut value = json!({ "user": { "name": "bob" }, "tags": ["a"] });
+/// apply_json_patch(
+///     &mut value,
+///     &json!([
+///         { "op": "replace", "path": "/user/name", "value": "alice" },
+///         { "op": "add", "path": "/tags/-", "value": "b" },
+///     ]),
+/// )
+/// .unwrap();
+/// assert_eq!(value, json!({ "user": { "name": "alice" }, "tags": ["a", "b"] }));
+///
+/// let err = apply_json_patch(&mut value, &json!([{ "op": "remove", "path": "/tags/5" }]))
+///     .unwrap_err();
+/// assert_eq!(
+///     err.to_string(),
+///     r#"operation 0 of the patch failed at "/tags/5": index out of bounds"#
+/// );
+/// ```
+///
+/// [RFC 6902]: https://www.rfc-editor.org/rfc/rfc6902
+pub fn apply_json_patch(value: &mut Value, patch: &Value) -> Result<(), PatchError> {
+    let operations = patch.as_array().ok_or_else(|| PatchError {
+        operation: 0,
+        path: Path::Root,
+        reason: "patch is not an array".to_owned(),
+    })?;
+
+    let mut patched = value.clone();
+    for (idx, operation) in operations.iter().enumerate() {
+        apply_operation(&mut patched, operation).map_err(|(path, reason)| PatchError {
+            operation: idx,
+            path,
+            reason,
+        })?;
+    }
+    *value = patched;
+    Ok(())
+}
+
+/// Apply `patch` to `rhs`, and compare `lhs` with the result.
+///
+/// This makes it possible to compare a value with a fixture that only differs in a few places,
+/// without keeping a copy of the whole fixture around for every variation. A patch that can't be
+/// applied is returned as [`PatchedError::Patch`], and anything else as [`PatchedError::Compare`].
+///
+/// ```
+/// use serde_json_assert::{try_assert_json_matches_patched, CompareMode, Config};
+/// use serde_json::json;
+///
+/// let fixture = json!({ "id": 1, "status": "draft", "tags": [] });
+/// let response = json!({ "id": 1, "status": "published", "tags": ["news"] });
+///
+/// let result = try_assert_json_matches_patched(
+///     &response,
+///     &fixture,
+///     &json!([
+///         { "op": "replace", "path": "/status", "value": "published" },
+///         { "op": "add", "path": "/tags/0", "value": "news" },
+///     ]),
+///     &Config::new(CompareMode::Strict),
+/// );
+/// assert!(result.is_ok());
+/// ```
+pub fn try_assert_json_matches_patched<Lhs>(
+    lhs: &Lhs,
+    rhs: &Value,
+    patch: &Value,
+    config: &Config,
+) -> Result<(), PatchedError>
+where
+    Lhs: Serialize,
+{
+    let lhs = serde_json::to_value(lhs).map_err(Error::SerializeLhs)?;
+    let mut rhs = rhs.clone();
+    apply_json_patch(&mut rhs, patch).map_err(PatchedError::Patch)?;
+
+    let diffs = diff(&lhs, &rhs, config);
+    if diffs.is_empty() {
+        Ok(())
+    } else {
+        Err(Error::Differences(diffs.into_iter().map(Difference::from).collect()).into())
+    }
+}
+
+/// An error returned by [`try_assert_json_matches_patched`].
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum PatchedError {
+    /// The patch could not be applied to the right hand side value.
+    Patch(PatchError),
+    /// The values differ, or could not be converted to JSON.
+    Compare(Error),
+}
+
+impl PatchedError {
+    /// Returns the differences between the values, or an empty slice if they could not be
+    /// compared.
+    pub fn differences(&self) -> &[Difference] {
+        match self {
+            PatchedError::Compare(err) => err.differences(),
+            _ => &[],
+        }
+    }
+}
+
+impl From<Error> for PatchedError {
+    fn from(err: Error) -> Self {
+        PatchedError::Compare(err)
+    }
+}
+
+impl fmt::Display for PatchedError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PatchedError::Patch(err) => write!(f, "{}", err),
+            PatchedError::Compare(err) => write!(f, "{}", err),
+        }
+    }
+}
+
+impl error::Error for PatchedError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            PatchedError::Patch(err) => Some(err),
+            PatchedError::Compare(err) => Some(err),
+        }
+    }
+}
+
+/// An error returned by [`apply_json_patch`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct PatchError {
+    operation: usize,
+    path: Path,
+    reason: String,
+}
+
+impl PatchError {
+    /// Returns the index of the operation that failed.
+    pub fn operation(&self) -> usize {
+        self.operation
+    }
+
+    /// Returns the path in the patched value where the operation failed.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl fmt::Display for PatchError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "operation {} of the patch failed at {:?}: {}",
+            self.operation,
+            self.path.to_json_pointer(),
+            self.reason
+        )
+    }
+}
+
+impl error::Error for PatchError {}
+
+type OperationError = (Path, String);
+
+fn apply_operation(value: &mut Value, operation: &Value) -> Result<(), OperationError> {
+    let field = |name: &str| {
+        operation
+            .get(name)
+            .ok_or_else(|| (Path::Root, format!("missing {:?} member", name)))
+    };
+    let pointer = |name: &str| {
+        field(name)?
+            .as_str()
+            .ok_or_else(|| (Path::Root, format!("{:?} is not a string", name)))
+            .and_then(|pointer| parse_pointer(pointer).map_err(|reason| (Path::Root, reason)))
+    };
+
+    let op = field("op")?
+        .as_str()
+        .ok_or_else(|| (Path::Root, r#""op" is not a string"#.to_owned()))?;
+    let path = pointer("path")?;
+    match op {
+        "add" => add(value, &path, field("value")?.clone()),
+        "remove" => remove(value, &path).map(drop),
+        "replace" => {
+            *lookup(value, &path)? = field("value")?.clone();
+            Ok(())
+        }
+        "move" => {
+            let from = pointer("from")?;
+            if path.len() > from.len() && path.starts_with(&from) {
+                return Err((
+                    to_path(value, &from),
+                    "cannot move a value into itself".to_owned(),
+                ));
+            }
+            let moved = remove(value, &from)?;
+            add(value, &path, moved)
+        }
+        "copy" => {
+            let from = pointer("from")?;
+            let copied = lookup(value, &from)?.clone();
+            add(value, &path, copied)
+        }
+        "test" => {
+            let expected = field("value")?;
+            if test_eq(lookup(value, &path)?, expected) {
+                Ok(())
+            } else {
+                Err((to_path(value, &path), "test failed".to_owned()))
+            }
+        }
+        op => Err((Path::Root, format!("unknown operation {:?}", op))),
+    }
+}
+
+/// Compare values like the `test` operation does, where numbers are equal if their values are.
+fn test_eq(lhs: &Value, rhs: &Value) -> bool {
+    match (lhs, rhs) {
+        (Value::Number(lhs), Value::Number(rhs)) => lhs == rhs || lhs.as_f64() == rhs.as_f64(),
+        (Value::Array(lhs), Value::Array(rhs)) => {
+            lhs.len() == rhs.len() && lhs.iter().zip(rhs).all(|(lhs, rhs)| test_eq(lhs, rhs))
+        }
+        (Value::Object(lhs), Value::Object(rhs)) => {
+            lhs.len() == rhs.len()
+                && lhs
+                    .iter()
+                    .all(|(key, lhs)| rhs.get(key).is_some_and(|rhs| test_eq(lhs, rhs)))
+        }
+        _ => lhs == rhs,
+    }
+}
+
+/// Split a JSON Pointer into its unescaped reference tokens.
+pub(crate) fn parse_pointer(pointer: &str) -> Result<Vec<String>, String> {
+    if pointer.is_empty() {
+        return Ok(vec![]);
+    }
+    let rest = pointer
+        .strip_prefix('/')
+        .ok_or_else(|| format!("invalid JSON pointer {:?}", pointer))?;
+    rest.split('/')
+        .map(|token| {
+            let mut unescaped = String::new();
+            let mut chars = token.chars();
+            while let Some(c) = chars.next() {
+                if c != '~' {
+                    unescaped.push(c);
+                    continue;
+                }
+                match chars.next() {
+                    Some('0') => unescaped.push('~'),
+                    Some('1') => unescaped.push('/'),
+                    _ => return Err(format!("invalid escape in JSON pointer {:?}", pointer)),
+                }
+            }
+            Ok(unescaped)
+        })
+        .collect()
+}
+
+/// Returns the [`Path`] that `tokens` refer to in `value`, treating tokens as indexes where they
+/// address an array.
+fn to_path(value: &Value, tokens: &[String]) -> Path {
+    let mut keys = vec![];
+    let mut current = Some(value);
+    for token in tokens {
+        let key = match (current, array_index(token)) {
+            (Some(Value::Array(_)), Some(idx)) => Key::Idx(idx),
+            _ => Key::Field(token.clone()),
+        };
+        current = current.and_then(|value| match (value, &key) {
+            (Value::Array(array), Key::Idx(idx)) => array.get(*idx),
+            (Value::Object(map), Key::Field(field)) => map.get(field),
+            _ => None,
+        });
+        keys.push(key);
+    }
+    if keys.is_empty() {
+        Path::Root
+    } else {
+        Path::Keys(keys)
+    }
+}
+
+/// Parses an array index, which can't have leading zeros.
+pub(crate) fn array_index(token: &str) -> Option<usize> {
+    let is_valid = token == "0"
+        || (!token.starts_with('0')
+            && !token.is_empty()
+            && token.bytes().all(|b| b.is_ascii_digit()));
+    if is_valid {
+        token.parse().ok()
+    } else {
+        None
+    }
+}
+
+fn lookup<'v>(value: &'v mut Value, tokens: &[String]) -> Result<&'v mut Value, OperationError> {
+    let path = to_path(value, tokens);
+    let mut current = value;
+    for token in tokens {
+        current = match current {
+            Value::Object(map) => map.get_mut(token),
+            Value::Array(array) => array_index(token).and_then(|idx| array.get_mut(idx)),
+            _ => None,
+        }
+        .ok_or_else(|| (path.clone(), "no value at this path".to_owned()))?;
+    }
+    Ok(current)
+}
+
+fn add(value: &mut Value, tokens: &[String], new: Value) -> Result<(), OperationError> {
+    let Some((last, parent)) = tokens.split_last() else {
+        *value = new;
+        return Ok(());
+    };
+    let path = to_path(value, tokens);
+    match lookup(value, parent)? {
+        Value::Object(map) => {
+            map.insert(last.clone(), new);
+        }
+        Value::Array(array) if last == "-" => array.push(new),
+        Value::Array(array) => match array_index(last) {
+            Some(idx) if idx <= array.len() => array.insert(idx, new),
+            Some(_) => return Err((path, "index out of bounds".to_owned())),
+            None => return Err((path, format!("invalid array index {:?}", last))),
+        },
+        _ => return Err((path, "parent is not an object or array".to_owned())),
+    }
+    Ok(())
+}
+
+fn remove(value: &mut Value, tokens: &[String]) -> Result<Value, OperationError> {
+    let Some((last, parent)) = tokens.split_last() else {
+        return Err((Path::Root, "cannot remove the root".to_owned()));
+    };
+    let path = to_path(value, tokens);
+    let removed = match lookup(value, parent)? {
+        Value::Object(map) => map.remove(last),
+        Value::Array(array) => match array_index(last) {
+            Some(idx) if idx < array.len() => Some(array.remove(idx)),
+            Some(_) => return Err((path, "index out of bounds".to_owned())),
+            None => return Err((path, format!("invalid array index {:?}", last))),
+        },
+        _ => None,
+    };
+    removed.ok_or_else(|| (path, "no value at this path".to_owned()))
+}
+
+fn parent(path: &Path) -> Path {
+    match path.keys().split_last() {
+        Some((_, [])) | None => Path::Root,
+        Some((_, parent)) => Path::Keys(parent.to_vec()),
+    }
+}
+
+fn is_keyed(path: &Path) -> bool {
+    matches!(path.keys().last(), Some(Key::Keyed { .. }))
+}
+
+/// Orders paths key by key, with indexes compared as numbers.
+fn sort_key(path: &Path) -> Vec<(usize, &str)> {
+    path.keys()
+        .iter()
+        .map(|key| match key {
+            Key::Field(field) => (0, field.as_str()),
+            Key::Idx(idx) | Key::Keyed { idx, .. } => (*idx, ""),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{try_assert_json_matches, CompareMode, Config, Matcher};
+
+    fn patch(actual: &Value, expected: &Value, config: &Config) -> Value {
+        match try_assert_json_matches(actual, expected, config) {
+            Ok(()) => json!([]),
+            Err(err) => json_patch(err.differences()),
+        }
+    }
+
+    #[test]
+    fn test_json_pointer() {
+        assert_eq!(Path::Root.to_json_pointer(), "");
+        let path = Path::Keys(vec![
+            Key::Field("a/b".to_owned()),
+            Key::Idx(3),
+            Key::Field("m~n".to_owned()),
+            Key::Field(String::new()),
+        ]);
+        assert_eq!(path.to_json_pointer(), "/a~1b/3/m~0n/");
+    }
+
+    #[test]
+    fn test_root() {
+        let config = Config::new(CompareMode::Strict);
+        assert_eq!(
+            patch(&json!(1), &json!("a"), &config),
+            json!([{ "op": "replace", "path": "", "value": "a" }])
+        );
+    }
+
+    #[test]
+    fn test_inclusive() {
+        let config = Config::new(CompareMode::Inclusive);
+        let actual = json!({ "a": { "b": 1, "extra": 2 }, "list": [1] });
+        let expected = json!({ "a": { "b": 2, "c": { "d": 3 } }, "list": [1, 2, 3] });
+        assert_eq!(
+            patch(&actual, &expected, &config),
+            json!([
+                { "op": "replace", "path": "/a/b", "value": 2 },
+                { "op": "add", "path": "/a/c", "value": { "d": 3 } },
+                { "op": "add", "path": "/list/1", "value": 2 },
+                { "op": "add", "path": "/list/2", "value": 3 },
+            ])
+        );
+    }
+
+    #[test]
+    fn test_aligned_arrays() {
+        let config = Config::new(CompareMode::Strict).align_arrays(true);
+        let actual = json!([0, 1, 2, 3, 4]);
+        let expected = json!([0, 9, 2, 4, 5]);
+        assert_eq!(
+            patch(&actual, &expected, &config),
+            json!([
+                { "op": "replace", "path": "/1", "value": 9 },
+                { "op": "remove", "path": "/3" },
+                { "op": "add", "path": "/4", "value": 5 },
+            ])
+        );
+    }
+
+    #[test]
+    fn test_unordered_and_keyed_arrays() {
+        let config = Config::new(CompareMode::Strict)
+            .path_config(".set", |config| config.consider_array_sorting(false))
+            .path_config(".users", |config| config.match_arrays_by_key(["id"]));
+        let actual = json!({
+            "set": [1, 2, 3],
+            "users": [{ "id": 1, "name": "bob" }, { "id": 2 }],
+        });
+        let expected = json!({
+            "set": [3, 4, 1],
+            "users": [{ "id": 1, "name": "alice" }, { "id": 3 }],
+        });
+        let patch = patch(&actual, &expected, &config);
+        let mut ops = patch.as_array().unwrap().clone();
+        ops.sort_by_key(|op| op.to_string());
+        assert_eq!(
+            ops,
+            vec![
+                json!({ "op": "add", "path": "/users/-", "value": { "id": 3 } }),
+                json!({ "op": "remove", "path": "/users/1" }),
+                json!({ "op": "replace", "path": "/set/1", "value": 4 }),
+                json!({ "op": "replace", "path": "/users/0/name", "value": "alice" }),
+            ]
+        );
+    }
+
+    fn merge_patch(actual: &Value, expected: &Value, config: &Config) -> Value {
+        match try_assert_json_matches(actual, expected, config) {
+            Ok(()) => json_merge_patch(actual, &[]),
+            Err(err) => json_merge_patch(actual, err.differences()),
+        }
+        .unwrap()
+    }
+
+    #[test]
+    fn test_merge_patch() {
+        let config = Config::new(CompareMode::Strict);
+        assert_eq!(
+            merge_patch(&json!({ "a": 1 }), &json!({ "a": 1 }), &config),
+            json!({})
+        );
+        assert_eq!(merge_patch(&json!(1), &json!([2]), &config), json!([2]));
+        assert_eq!(merge_patch(&json!(1), &json!(1), &config), json!(1));
+        assert_eq!(merge_patch(&json!([1]), &json!([1]), &config), json!([1]));
+        assert_eq!(
+            merge_patch(
+                &json!({ "a": { "b": 1, "c": 2 }, "d": [1, 2], "e": 3 }),
+                &json!({ "a": { "b": 1, "c": 3 }, "d": [1, 2, 3], "f": { "g": 1 } }),
+                &config,
+            ),
+            json!({ "a": { "c": 3 }, "d": [1, 2, 3], "e": null, "f": { "g": 1 } })
+        );
+
+        // Data that actual may contain in addition to expected is kept.
+        let config = Config::new(CompareMode::Inclusive);
+        assert_eq!(
+            merge_patch(
+                &json!({ "a": 1, "b": [{ "c": 1, "d": 2 }] }),
+                &json!({ "b": [{ "c": 2 }] }),
+                &config,
+            ),
+            json!({ "b": [{ "c": 2, "d": 2 }] })
+        );
+    }
+
+    fn apply(mut value: Value, patch: Value) -> Result<Value, String> {
+        apply_json_patch(&mut value, &patch)
+            .map(|()| value)
+            .map_err(|err| err.to_string())
+    }
+
+    #[test]
+    fn test_apply_json_patch() {
+        let value = json!({ "a": { "b": [1, 2] }, "c~/d": 3 });
+        assert_eq!(
+            apply(
+                value.clone(),
+                json!([
+                    { "op": "add", "path": "/a/b/1", "value": 9 },
+                    { "op": "remove", "path": "/c~0~1d" },
+                    { "op": "replace", "path": "/a/e", "value": null },
+                ])
+            ),
+            Err(r#"operation 2 of the patch failed at "/a/e": no value at this path"#.to_owned())
+        );
+        assert_eq!(
+            apply(
+                value.clone(),
+                json!([
+                    { "op": "add", "path": "/a/b/1", "value": 9 },
+                    { "op": "remove", "path": "/c~0~1d" },
+                    { "op": "move", "from": "/a/b", "path": "/b" },
+                    { "op": "copy", "from": "/b/0", "path": "/b/-" },
+                    { "op": "test", "path": "/b", "value": [1, 9, 2, 1] },
+                    { "op": "replace", "path": "", "value": { "x": 1 } },
+                ])
+            ),
+            Ok(json!({ "x": 1 }))
+        );
+
+        assert_eq!(
+            apply(
+                value.clone(),
+                json!([{ "op": "test", "path": "/a/b/0", "value": 2 }])
+            ),
+            Err(r#"operation 0 of the patch failed at "/a/b/0": test failed"#.to_owned())
+        );
+
+        // Numbers are compared by value, also inside arrays and objects.
+        assert_eq!(
+            apply(
+                json!({ "n": 1, "m": [{ "x": 2.0 }] }),
+                json!([
+                    { "op": "test", "path": "/n", "value": 1.0 },
+                    { "op": "test", "path": "/m", "value": [{ "x": 2 }] },
+                ])
+            ),
+            Ok(json!({ "n": 1, "m": [{ "x": 2.0 }] }))
+        );
+        assert!(apply(
+            json!({ "n": 1 }),
+            json!([{ "op": "test", "path": "/n", "value": 1.5 }])
+        )
+        .is_err());
+        assert_eq!(
+            apply(
+                value.clone(),
+                json!([{ "op": "add", "path": "/a/b/01", "value": 2 }])
+            ),
+            Err(
+                r#"operation 0 of the patch failed at "/a/b/01": invalid array index "01""#
+                    .to_owned()
+            )
+        );
+        assert_eq!(
+            apply(
+                value.clone(),
+                json!([{ "op": "move", "from": "/a", "path": "/a/f" }])
+            ),
+            Err(
+                r#"operation 0 of the patch failed at "/a": cannot move a value into itself"#
+                    .to_owned()
+            )
+        );
+        assert!(apply(value.clone(), json!([{ "op": "frobnicate", "path": "" }])).is_err());
+        assert!(apply(value.clone(), json!([{ "op": "add", "path": "a" }])).is_err());
+        assert!(apply(value, json!({})).is_err());
+
+        // The error path uses indexes for arrays and fields for objects.
+        let mut value = json!({ "0": [[]] });
+        let err = apply_json_patch(&mut value, &json!([{ "op": "remove", "path": "/0/0/3" }]))
+            .unwrap_err();
+        assert_eq!(
+            err.path(),
+            &Path::Keys(vec![Key::Field("0".to_owned()), Key::Idx(0), Key::Idx(3)])
+        );
+        assert_eq!(value, json!({ "0": [[]] }));
+    }
+
+    #[test]
+    fn test_generated_patch_applies() {
+        let config = Config::new(CompareMode::Strict).align_arrays(true);
+        let actual = json!({ "a": [0, 1, 2, 3, 4], "b": { "c": 1 }, "d": 1 });
+        let expected = json!({ "a": [0, 9, 2, 4, 5], "b": { "e": [] }, "f": 1 });
+        let err = try_assert_json_matches(&actual, &expected, &config).unwrap_err();
+        assert_eq!(apply(actual, json_patch(err.differences())), Ok(expected));
+    }
+
+    #[test]
+    fn test_matches_patched() {
+        let config = Config::new(CompareMode::Strict);
+        let fixture = json!({ "a": 1, "b": 2 });
+        let patch = json!([{ "op": "replace", "path": "/a", "value": 3 }]);
+        let result =
+            try_assert_json_matches_patched(&json!({ "a": 3, "b": 2 }), &fixture, &patch, &config);
+        assert!(result.is_ok());
+
+        let result =
+            try_assert_json_matches_patched(&json!({ "a": 1, "b": 2 }), &fixture, &patch, &config);
+        match result {
+            Err(PatchedError::Compare(Error::Differences(differences))) => {
+                assert_eq!(differences.len(), 1)
+            }
+            other => panic!("unexpected result: {:?}", other),
+        }
+
+        let patch = json!([{ "op": "remove", "path": "/c" }]);
+        let result = try_assert_json_matches_patched(&json!({}), &fixture, &patch, &config);
+        match result {
+            Err(PatchedError::Patch(err)) => {
+                assert_eq!(err.path(), &Path::Keys(vec![Key::Field("c".to_owned())]))
+            }
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_matchers_are_skipped() {
+        let config = Config::new(CompareMode::Strict);
+        let actual = json!({ "id": "1", "n": 1 });
+        let expected = json!({ "id": Matcher::AnyNumber, "n": 2 });
+        assert_eq!(
+            patch(&actual, &expected, &config),
+            json!([{ "op": "replace", "path": "/n", "value": 2 }])
+        );
+
+        // Matchers inside missing values can't be added either, while escaped ones are written
+        // as the strings they stand for.
+        let actual = json!({ "a": 1 });
+        let expected = json!({
+            "a": Matcher::escape("{{any}}"),
+            "b": { "id": Matcher::AnyNumber },
+            "c": [Matcher::escape("{{any}}")],
+        });
+        assert_eq!(
+            patch(&actual, &expected, &config),
+            json!([
+                { "op": "replace", "path": "/a", "value": "{{any}}" },
+                { "op": "add", "path": "/c", "value": ["{{any}}"] },
+            ])
+        );
+    }
+
+    #[test]
+    fn test_merge_patch_of_other_differences() {
+        let config = Config::new(CompareMode::Strict);
+        let err = try_assert_json_matches(&json!({ "a": [1] }), &json!({ "a": [] }), &config)
+            .unwrap_err();
+        let err = json_merge_patch(&json!({ "b": 1 }), err.differences()).unwrap_err();
+        assert_eq!(err.path(), &Path::Keys(vec![Key::Field("a".to_owned())]));
+    }
+}
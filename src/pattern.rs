@@ -0,0 +1,448 @@
+use crate::diff::{KeyRef, PathRef};
+use crate::{Key, Path};
+use serde_json::Value;
+use std::{error::Error, fmt, str::FromStr};
+
+/// A pattern describing a family of paths in a JSON tree.
+///
+/// Patterns are written the way paths are printed in error messages, such as
+/// `.data.users[0].name`, with a few additions:
+///
+/// - `.*` matches any field of an object.
+/// - `[*]` matches any index of an array.
+/// - `.**` matches any number of fields and indexes, including none.
+/// - `["odd.key"]` matches a field whose name would otherwise need escaping. The name is written as
+///   a JSON string.
+/// - `[2:id=42]` is an element of an array compared with
+///   [`Config::match_arrays_by_key`](crate::Config::match_arrays_by_key), as printed in error
+///   messages. It matches the index before the `:`, like `[2]`.
+///
+/// `""` and `"(root)"` both refer to the root of the tree.
+///
+/// ```
+/// use serde_json_assert::{Key, Path, PathPattern};
+///
+/// let pattern: PathPattern = ".**.created_at".parse().unwrap();
+///
+/// let path = Path::Keys(vec![
+///     Key::Field("items".to_owned()),
+///     Key::Idx(3),
+///     Key::Field("created_at".to_owned()),
+/// ]);
+/// assert!(pattern.matches(&path));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct PathPattern {
+    segments: Vec<Segment>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+enum Segment {
+    /// A field in a JSON object.
+    Field(String),
+    /// An index in a JSON array.
+    Idx(usize),
+    /// An element of a JSON array paired by key, written `[idx:id]`. Matched by its index.
+    Keyed { idx: usize, id: String },
+    /// Any field in a JSON object, written `.*`.
+    AnyField,
+    /// Any index in a JSON array, written `[*]`.
+    AnyIdx,
+    /// Any number of keys, written `.**`.
+    AnyDepth,
+}
+
+impl PathPattern {
+    /// Returns `true` if `path` is one of the paths described by this pattern.
+    pub fn matches(&self, path: &Path) -> bool {
+        match path {
+            Path::Root => matches::<Key>(&self.segments, &[]),
+            Path::Keys(keys) => matches(&self.segments, keys),
+        }
+    }
+
+    pub(crate) fn matches_ref(&self, path: &PathRef<'_>) -> bool {
+        matches(&self.segments, path.keys())
+    }
+
+    /// Returns `true` if `path` or one of its ancestors is described by this pattern.
+    pub(crate) fn matches_ancestor_ref(&self, path: &PathRef<'_>) -> bool {
+        let keys = path.keys();
+        (0..=keys.len()).any(|len| matches(&self.segments, &keys[..len]))
+    }
+}
+
+fn matches<K: PathKey>(segments: &[Segment], keys: &[K]) -> bool {
+    match (segments.split_first(), keys.split_first()) {
+        (None, _) => keys.is_empty(),
+        (Some((Segment::AnyDepth, rest)), _) => {
+            matches(rest, keys) || (!keys.is_empty() && matches(segments, &keys[1..]))
+        }
+        (Some(_), None) => false,
+        (Some((segment, rest)), Some((key, keys))) => segment.matches(key) && matches(rest, keys),
+    }
+}
+
+/// The parts of a key that patterns are matched against.
+trait PathKey {
+    fn field(&self) -> Option<&str>;
+    fn idx(&self) -> Option<usize>;
+}
+
+impl PathKey for KeyRef<'_> {
+    fn field(&self) -> Option<&str> {
+        match self {
+            KeyRef::Field(field) => Some(field),
+            KeyRef::Idx(_) | KeyRef::Keyed { .. } => None,
+        }
+    }
+
+    fn idx(&self) -> Option<usize> {
+        match self {
+            KeyRef::Idx(idx) | KeyRef::Keyed { idx, .. } => Some(*idx),
+            KeyRef::Field(_) => None,
+        }
+    }
+}
+
+impl PathKey for Key {
+    fn field(&self) -> Option<&str> {
+        match self {
+            Key::Field(field) => Some(field),
+            Key::Idx(_) | Key::Keyed { .. } => None,
+        }
+    }
+
+    fn idx(&self) -> Option<usize> {
+        match self {
+            Key::Idx(idx) | Key::Keyed { idx, .. } => Some(*idx),
+            Key::Field(_) => None,
+        }
+    }
+}
+
+impl Segment {
+    fn matches<K: PathKey>(&self, key: &K) -> bool {
+        match self {
+            Segment::Field(field) => key.field() == Some(field),
+            Segment::Idx(idx) | Segment::Keyed { idx, .. } => key.idx() == Some(*idx),
+            Segment::AnyField => key.field().is_some(),
+            Segment::AnyIdx => key.idx().is_some(),
+            Segment::AnyDepth => true,
+        }
+    }
+}
+
+impl FromStr for PathPattern {
+    type Err = ParsePathError;
+
+    fn from_str(path: &str) -> Result<Self, Self::Err> {
+        let error = |reason: String| ParsePathError::new(path, reason);
+
+        let mut segments = vec![];
+        if path == "(root)" {
+            return Ok(PathPattern { segments });
+        }
+
+        let mut rest = path;
+        while let Some(c) = rest.chars().next() {
+            match c {
+                '.' => {
+                    let end = rest[1..].find(['.', '[']).map_or(rest.len(), |end| end + 1);
+                    let segment = match &rest[1..end] {
+                        "" => return Err(error("empty field name".to_owned())),
+                        "*" => Segment::AnyField,
+                        "**" => Segment::AnyDepth,
+                        field => Segment::Field(field.to_owned()),
+                    };
+                    segments.push(segment);
+                    rest = &rest[end..];
+                }
+                '[' if rest[1..].starts_with('"') => {
+                    let end = quoted_len(&rest[1..])
+                        .ok_or_else(|| error("unterminated string".to_owned()))?
+                        + 1;
+                    let field = serde_json::from_str(&rest[1..end])
+                        .map_err(|err| error(format!("invalid field name: {}", err)))?;
+                    if !rest[end..].starts_with(']') {
+                        return Err(error("expected ']' after field name".to_owned()));
+                    }
+                    segments.push(Segment::Field(field));
+                    rest = &rest[end + 1..];
+                }
+                '[' => {
+                    let digits = rest[1..]
+                        .find(|c: char| !c.is_ascii_digit())
+                        .map_or(rest.len() - 1, |digits| digits);
+                    let (segment, end) = if digits > 0 && rest[1 + digits..].starts_with(':') {
+                        let start = digits + 2;
+                        let end = keyed_id_len(&rest[start..])
+                            .map(|len| start + len)
+                            .ok_or_else(|| error("unclosed '['".to_owned()))?;
+                        if end == start {
+                            return Err(error("empty key values".to_owned()));
+                        }
+                        let idx = rest[1..=digits].parse().map_err(|_| {
+                            error(format!("invalid array index {:?}", &rest[1..=digits]))
+                        })?;
+                        let id = rest[start..end].to_owned();
+                        (Segment::Keyed { idx, id }, end)
+                    } else {
+                        let end = rest
+                            .find(']')
+                            .ok_or_else(|| error("unclosed '['".to_owned()))?;
+                        let segment = match &rest[1..end] {
+                            "*" => Segment::AnyIdx,
+                            index => index
+                                .parse()
+                                .map(Segment::Idx)
+                                .map_err(|_| error(format!("invalid array index {:?}", index)))?,
+                        };
+                        (segment, end)
+                    };
+                    segments.push(segment);
+                    rest = &rest[end + 1..];
+                }
+                _ => return Err(error(format!("expected '.' or '[' at {:?}", rest))),
+            }
+        }
+
+        Ok(PathPattern { segments })
+    }
+}
+
+/// Parse a path written like a [`PathPattern`] without wildcards, or as a JSON Pointer.
+pub(crate) fn parse_path(path: &str) -> Result<Path, ParsePathError> {
+    if path.starts_with('/') {
+        return Path::from_json_pointer(path);
+    }
+
+    let keys =
+        path.parse::<PathPattern>()?
+            .segments
+            .into_iter()
+            .map(|segment| match segment {
+                Segment::Field(field) => Ok(Key::Field(field)),
+                Segment::Idx(idx) => Ok(Key::Idx(idx)),
+                Segment::Keyed { idx, id } => Ok(Key::Keyed { idx, id }),
+                Segment::AnyField | Segment::AnyIdx | Segment::AnyDepth => Err(
+                    ParsePathError::new(path, "wildcards are not allowed in a path".to_owned()),
+                ),
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+    Ok(if keys.is_empty() {
+        Path::Root
+    } else {
+        Path::Keys(keys)
+    })
+}
+
+/// Returns the length of the key values of a keyed element at the start of `s`, up to the `]` that
+/// closes the element. Brackets and braces of JSON values, and anything in JSON strings, are
+/// skipped.
+fn keyed_id_len(s: &str) -> Option<usize> {
+    let mut depth = 0;
+    let mut in_string = false;
+    let mut escaped = false;
+    for (idx, c) in s.char_indices() {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' if in_string => escaped = true,
+            '"' => in_string = !in_string,
+            _ if in_string => {}
+            '[' | '{' => depth += 1,
+            ']' if depth == 0 => return Some(idx),
+            ']' | '}' => depth -= 1,
+            _ => {}
+        }
+    }
+    None
+}
+
+/// Returns the length of the JSON string literal at the start of `s`, including both quotes.
+fn quoted_len(s: &str) -> Option<usize> {
+    let mut escaped = false;
+    for (idx, c) in s.char_indices().skip(1) {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' => escaped = true,
+            '"' => return Some(idx + 1),
+            _ => {}
+        }
+    }
+    None
+}
+
+impl TryFrom<&str> for PathPattern {
+    type Error = ParsePathError;
+
+    fn try_from(path: &str) -> Result<Self, Self::Error> {
+        path.parse()
+    }
+}
+
+impl From<Path> for PathPattern {
+    fn from(path: Path) -> Self {
+        let segments = match path {
+            Path::Root => vec![],
+            Path::Keys(keys) => keys
+                .into_iter()
+                .map(|key| match key {
+                    Key::Field(field) => Segment::Field(field),
+                    Key::Idx(idx) => Segment::Idx(idx),
+                    Key::Keyed { idx, id } => Segment::Keyed { idx, id },
+                })
+                .collect(),
+        };
+        PathPattern { segments }
+    }
+}
+
+impl fmt::Display for PathPattern {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.segments.is_empty() {
+            return write!(f, "(root)");
+        }
+        for segment in &self.segments {
+            match segment {
+                Segment::Field(field) => write_field(f, field)?,
+                Segment::Idx(idx) => write!(f, "[{}]", idx)?,
+                Segment::Keyed { idx, id } => write!(f, "[{}:{}]", idx, id)?,
+                Segment::AnyField => write!(f, ".*")?,
+                Segment::AnyIdx => write!(f, "[*]")?,
+                Segment::AnyDepth => write!(f, ".**")?,
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Write a field as `.field`, or as `["field"]` if it couldn't be parsed back otherwise.
+pub(crate) fn write_field(f: &mut fmt::Formatter, field: &str) -> fmt::Result {
+    let is_plain =
+        !field.is_empty() && field != "*" && field != "**" && !field.contains(['.', '[', ']', '"']);
+    if is_plain {
+        write!(f, ".{}", field)
+    } else {
+        write!(f, "[{}]", Value::from(field))
+    }
+}
+
+/// An error returned when parsing a [`PathPattern`] or a [`Path`] fails.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParsePathError {
+    path: String,
+    reason: String,
+}
+
+impl ParsePathError {
+    pub(crate) fn new(path: &str, reason: String) -> Self {
+        ParsePathError {
+            path: path.to_owned(),
+            reason,
+        }
+    }
+}
+
+impl fmt::Display for ParsePathError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "invalid path {:?}: {}", self.path, self.reason)
+    }
+}
+
+impl Error for ParsePathError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(path: &str) -> PathPattern {
+        path.parse().unwrap()
+    }
+
+    #[test]
+    fn test_parse() {
+        for path in [
+            "(root)",
+            ".a",
+            ".a.b",
+            "[0]",
+            ".a[1].b",
+            ".items[*].created_at",
+            ".*.b",
+            ".**.created_at",
+            ".a.**",
+            r#"["a.b"][0]["*"]["say \"hi\""]"#,
+            r#".users[3:id=42,name="a]b"][0]"#,
+            ".a[1:tags=[1,{\"x\":[]}]]",
+        ] {
+            assert_eq!(parse(path).to_string(), path);
+        }
+        assert_eq!(parse("").to_string(), "(root)");
+        assert_eq!(parse(r#"["a"]"#).to_string(), ".a");
+
+        assert!("a".parse::<PathPattern>().is_err());
+        assert!(".a..b".parse::<PathPattern>().is_err());
+        assert!(".a[".parse::<PathPattern>().is_err());
+        assert!(".a[x]".parse::<PathPattern>().is_err());
+        assert!(".a[1:]".parse::<PathPattern>().is_err());
+        assert!(".a[1:id=[2]".parse::<PathPattern>().is_err());
+        assert!(r#"["a"#.parse::<PathPattern>().is_err());
+        assert!(r#"["a"b"#.parse::<PathPattern>().is_err());
+
+        let err = ".a[x]".parse::<PathPattern>().unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            r#"invalid path ".a[x]": invalid array index "x""#
+        );
+    }
+
+    #[test]
+    fn test_matches() {
+        let root = PathRef::Root;
+        let a = root.append(KeyRef::Field("a"));
+        let a_0 = a.append(KeyRef::Idx(0));
+        let a_0_b = a_0.append(KeyRef::Field("b"));
+
+        let pattern = parse("(root)");
+        assert!(pattern.matches_ref(&root));
+        assert!(!pattern.matches_ref(&a));
+
+        let pattern = parse(".a[0].b");
+        assert!(pattern.matches_ref(&a_0_b));
+        assert!(!pattern.matches_ref(&a_0));
+
+        let pattern = parse(".a[*]");
+        assert!(pattern.matches_ref(&a_0));
+        assert!(!pattern.matches_ref(&a));
+        assert!(!pattern.matches_ref(&a_0_b));
+
+        let pattern = parse(".a[1]");
+        assert!(!pattern.matches_ref(&a_0));
+
+        let pattern = parse(".*");
+        assert!(pattern.matches_ref(&a));
+        assert!(!pattern.matches_ref(&root.append(KeyRef::Idx(0))));
+
+        let pattern = parse(".**.b");
+        assert!(pattern.matches_ref(&a_0_b));
+        assert!(pattern.matches_ref(&root.append(KeyRef::Field("b"))));
+        assert!(!pattern.matches_ref(&a_0));
+
+        let pattern = parse(".**");
+        assert!(pattern.matches_ref(&root));
+        assert!(pattern.matches_ref(&a_0_b));
+
+        let pattern = parse(".a.**[0]");
+        assert!(pattern.matches_ref(&a_0));
+        assert!(!pattern.matches_ref(&a_0_b));
+    }
+
+    #[test]
+    fn test_from_path() {
+        let path = Path::Keys(vec![Key::Field("a.b".to_owned()), Key::Idx(1)]);
+        let pattern = PathPattern::from(path.clone());
+        assert!(pattern.matches(&path));
+        assert_eq!(pattern.to_string(), r#"["a.b"][1]"#);
+    }
+}
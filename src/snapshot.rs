@@ -0,0 +1,478 @@
+use crate::diff::{diff, matches};
+use crate::{color, compare, Config, Difference, Error, Key, Matcher, Path as JsonPath};
+use serde::Serialize;
+use serde_json::Value;
+use std::{error, fmt, fs, io, path::Path, path::PathBuf};
+
+/// The environment variable that makes snapshot assertions rewrite their snapshot files.
+pub(crate) const UPDATE_VAR: &str = "SERDE_JSON_ASSERT_UPDATE";
+
+/// Compares a value against a JSON snapshot file without panicking.
+///
+/// The file is read as the expected value and compared with `value` according to `config`, so
+/// snapshots may contain [`Matcher`](crate::Matcher) placeholders and can be compared inclusively.
+///
+/// When the `SERDE_JSON_ASSERT_UPDATE` environment variable is set to anything but `0`, `false`,
+/// `no` or `off`, a missing or mismatched snapshot is written instead, and `Ok(())` is returned.
+/// The file is rewritten with the pretty-printed value. Matchers of the old snapshot are kept
+/// where they still accept the new value, and the values at paths redacted with
+/// [`Config::redact`] are written as placeholders. Snapshots that already match are left
+/// untouched.
+///
+/// Relative paths are resolved against the current directory. Use
+/// [`assert_json_snapshot`](macro.assert_json_snapshot.html) to resolve them against the crate
+/// root instead.
+///
+/// ```
+/// use serde_json_assert::{try_assert_json_snapshot, CompareMode, Config};
+/// use serde_json::json;
+///
+/// let path = std::env::temp_dir().join("serde_json_assert_doc_snapshot.json");
+/// std::fs::write(&path, r#"{ "id": "{{any_number}}", "name": "bob" }"#).unwrap();
+///
+/// let config = Config::new(CompareMode::Strict);
+/// let result = try_assert_json_snapshot(&json!({ "id": 1, "name": "bob" }), &path, &config);
+/// assert!(result.is_ok());
+///
+/// let err = try_assert_json_snapshot(&json!({ "id": 1, "name": "rob" }), &path, &config)
+///     .unwrap_err();
+/// assert_eq!(err.differences().len(), 1);
+/// # std::fs::remove_file(&path).unwrap();
+/// ```
+///
+/// A snapshot file that can't be read, parsed or written is returned as
+/// [`SnapshotAssertError::Snapshot`], and anything else as [`SnapshotAssertError::Compare`].
+pub fn try_assert_json_snapshot<T>(
+    value: &T,
+    path: impl AsRef<Path>,
+    config: &Config,
+) -> Result<(), SnapshotAssertError>
+where
+    T: Serialize,
+{
+    let value = serde_json::to_value(value).map_err(Error::SerializeLhs)?;
+    let path = path.as_ref();
+    let Some(snapshot) = read_or_update(&value, path, config, update_enabled())? else {
+        return Ok(());
+    };
+
+    let diffs = diff(&value, &snapshot, config);
+    if diffs.is_empty() {
+        Ok(())
+    } else {
+        Err(Error::Differences(diffs.into_iter().map(Difference::from).collect()).into())
+    }
+}
+
+/// Compares a value against a snapshot file like [`try_assert_json_snapshot`], but returns the
+/// failure message of the assertion macros. Used by
+/// [`assert_json_snapshot`](macro.assert_json_snapshot.html).
+#[doc(hidden)]
+pub fn __assert_json_snapshot_message<T>(
+    value: &T,
+    path: &Path,
+    config: &Config,
+) -> Result<(), String>
+where
+    T: Serialize,
+{
+    let value = serde_json::to_value(value).map_err(|err| Error::SerializeLhs(err).to_string())?;
+    let Some(snapshot) =
+        read_or_update(&value, path, config, update_enabled()).map_err(|err| err.to_string())?
+    else {
+        return Ok(());
+    };
+
+    compare(&value, &snapshot, config, color::enabled()).map_err(|msg| {
+        format!(
+            "snapshot {} does not match:\n\n{}\n\nRun with {}=1 to update it.",
+            path.display(),
+            msg,
+            UPDATE_VAR
+        )
+    })
+}
+
+fn update_enabled() -> bool {
+    std::env::var(UPDATE_VAR).is_ok_and(|value| is_enabled(&value))
+}
+
+/// Whether `value`, read from [`UPDATE_VAR`], turns updates on.
+fn is_enabled(value: &str) -> bool {
+    !matches!(
+        value.trim().to_ascii_lowercase().as_str(),
+        "" | "0" | "false" | "no" | "off"
+    )
+}
+
+/// Read the snapshot at `path`. In update mode the snapshot is rewritten with `value` if it is
+/// missing or doesn't match, and `None` is returned.
+fn read_or_update(
+    value: &Value,
+    path: &Path,
+    config: &Config,
+    update: bool,
+) -> Result<Option<Value>, SnapshotError> {
+    match read(path) {
+        Ok(snapshot) if !update || matches(value, &snapshot, config) => Ok(Some(snapshot)),
+        Err(err) if !update => Err(err),
+        snapshot => {
+            write(value, snapshot.ok().as_ref(), path, config)?;
+            Ok(None)
+        }
+    }
+}
+
+fn read(path: &Path) -> Result<Value, SnapshotError> {
+    let error = |source| SnapshotError::new(path, false, source);
+    let contents = fs::read_to_string(path).map_err(error)?;
+    serde_json::from_str(&contents).map_err(|err| error(err.into()))
+}
+
+fn write(
+    value: &Value,
+    old: Option<&Value>,
+    path: &Path,
+    config: &Config,
+) -> Result<(), SnapshotError> {
+    let mut value = value.clone();
+    if let Some(old) = old {
+        keep_matchers(&mut value, old);
+    }
+    redact(&mut value, config, &mut vec![]);
+
+    let error = |source| SnapshotError::new(path, true, source);
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(error)?;
+    }
+    let mut contents = serde_json::to_string_pretty(&value).map_err(|err| error(err.into()))?;
+    contents.push('\n');
+    fs::write(path, contents).map_err(error)
+}
+
+/// Put the matchers of the `old` snapshot back into `value` wherever they still accept it, so
+/// rewriting a snapshot doesn't replace them with the values they matched.
+fn keep_matchers(value: &mut Value, old: &Value) {
+    if let Some(matcher) = Matcher::from_value(old) {
+        if matcher.matches(value) {
+            *value = old.clone();
+        }
+        return;
+    }
+
+    match (value, old) {
+        (Value::Object(map), Value::Object(old)) => {
+            for (field, value) in map {
+                if let Some(old) = old.get(field) {
+                    keep_matchers(value, old);
+                }
+            }
+        }
+        (Value::Array(values), Value::Array(old)) => {
+            for (value, old) in values.iter_mut().zip(old) {
+                keep_matchers(value, old);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Replace the values at the paths redacted by `config` with the placeholders of their matchers.
+fn redact(value: &mut Value, config: &Config, keys: &mut Vec<Key>) {
+    let path = if keys.is_empty() {
+        JsonPath::Root
+    } else {
+        JsonPath::Keys(keys.clone())
+    };
+    if let Some(matcher) = config.redaction(&path) {
+        *value = matcher.clone().into();
+        return;
+    }
+
+    match value {
+        Value::Object(map) => {
+            for (field, value) in map {
+                keys.push(Key::Field(field.clone()));
+                redact(value, config, keys);
+                keys.pop();
+            }
+        }
+        Value::Array(values) => {
+            for (idx, value) in values.iter_mut().enumerate() {
+                keys.push(Key::Idx(idx));
+                redact(value, config, keys);
+                keys.pop();
+            }
+        }
+        _ => {}
+    }
+}
+
+/// An error returned by [`try_assert_json_snapshot`].
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum SnapshotAssertError {
+    /// The snapshot file could not be read, parsed or written.
+    Snapshot(SnapshotError),
+    /// The value differs from the snapshot, or could not be converted to JSON.
+    Compare(Error),
+}
+
+impl SnapshotAssertError {
+    /// Returns the differences between the value and the snapshot, or an empty slice if they
+    /// could not be compared.
+    pub fn differences(&self) -> &[Difference] {
+        match self {
+            SnapshotAssertError::Compare(err) => err.differences(),
+            _ => &[],
+        }
+    }
+}
+
+impl From<Error> for SnapshotAssertError {
+    fn from(err: Error) -> Self {
+        SnapshotAssertError::Compare(err)
+    }
+}
+
+impl From<SnapshotError> for SnapshotAssertError {
+    fn from(err: SnapshotError) -> Self {
+        SnapshotAssertError::Snapshot(err)
+    }
+}
+
+impl fmt::Display for SnapshotAssertError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SnapshotAssertError::Snapshot(err) => write!(f, "{}", err),
+            SnapshotAssertError::Compare(err) => write!(f, "{}", err),
+        }
+    }
+}
+
+impl error::Error for SnapshotAssertError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            SnapshotAssertError::Snapshot(err) => Some(err),
+            SnapshotAssertError::Compare(err) => Some(err),
+        }
+    }
+}
+
+/// An error returned when a snapshot file can't be read, parsed or written.
+#[derive(Debug)]
+pub struct SnapshotError {
+    path: PathBuf,
+    write: bool,
+    source: io::Error,
+}
+
+impl SnapshotError {
+    fn new(path: &Path, write: bool, source: io::Error) -> Self {
+        Self {
+            path: path.to_owned(),
+            write,
+            source,
+        }
+    }
+
+    /// Returns the path of the snapshot file.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl fmt::Display for SnapshotError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let action = if self.write { "write" } else { "read" };
+        write!(
+            f,
+            "Couldn't {} snapshot {}: {}",
+            action,
+            self.path.display(),
+            self.source
+        )?;
+        if !self.write && self.source.kind() == io::ErrorKind::NotFound {
+            write!(f, "\n\nRun with {}=1 to create it.", UPDATE_VAR)?;
+        }
+        Ok(())
+    }
+}
+
+impl error::Error for SnapshotError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        Some(&self.source)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{CompareMode, Matcher};
+    use serde_json::json;
+
+    /// A temporary directory for the snapshot of a test, which is removed when dropped so that
+    /// it is cleaned up even if the test fails.
+    struct SnapshotDir(PathBuf);
+
+    impl SnapshotDir {
+        fn new(name: &str) -> Self {
+            let dir = std::env::temp_dir().join(format!(
+                "serde_json_assert_{}_{}",
+                std::process::id(),
+                name
+            ));
+            let _ = fs::remove_dir_all(&dir);
+            fs::create_dir_all(&dir).unwrap();
+            SnapshotDir(dir)
+        }
+
+        fn path(&self) -> PathBuf {
+            self.0.join("snapshot.json")
+        }
+    }
+
+    impl Drop for SnapshotDir {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(&self.0);
+        }
+    }
+
+    #[test]
+    fn test_read_or_update() {
+        let config = Config::new(CompareMode::Strict);
+        let dir = SnapshotDir::new("read_or_update");
+        let path = dir.path();
+
+        let err = read_or_update(&json!(1), &path, &config, false).unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("Run with SERDE_JSON_ASSERT_UPDATE=1"));
+
+        // Missing snapshots are created in update mode.
+        let value = json!({ "a": [1, 2] });
+        assert_eq!(read_or_update(&value, &path, &config, true).unwrap(), None);
+        assert_eq!(
+            fs::read_to_string(&path).unwrap(),
+            "{\n  \"a\": [\n    1,\n    2\n  ]\n}\n"
+        );
+        assert_eq!(
+            read_or_update(&value, &path, &config, false).unwrap(),
+            Some(value)
+        );
+
+        // Matching snapshots are left as they are, so matchers survive.
+        fs::write(&path, r#"{ "a": "{{any_array}}" }"#).unwrap();
+        let other = json!({ "a": [3] });
+        assert!(read_or_update(&other, &path, &config, true)
+            .unwrap()
+            .is_some());
+        assert_eq!(
+            fs::read_to_string(&path).unwrap(),
+            r#"{ "a": "{{any_array}}" }"#
+        );
+
+        // Mismatched snapshots are only rewritten in update mode.
+        let other = json!({ "a": 3 });
+        assert!(read_or_update(&other, &path, &config, false)
+            .unwrap()
+            .is_some());
+        assert_eq!(read_or_update(&other, &path, &config, true).unwrap(), None);
+        assert_eq!(fs::read_to_string(&path).unwrap(), "{\n  \"a\": 3\n}\n");
+    }
+
+    #[test]
+    fn test_redact() {
+        let config = Config::new(CompareMode::Strict)
+            .redact(".token", Matcher::AnyString)
+            .redact(".items[*].created_at", Matcher::NotNull)
+            .redact(
+                "/items/1/created_at".parse::<JsonPath>().unwrap(),
+                Matcher::Any,
+            );
+        let dir = SnapshotDir::new("redact");
+        let path = dir.path();
+
+        let value = json!({
+            "token": "b5f1",
+            "items": [
+                { "id": 1, "created_at": "2025-06-13" },
+                { "id": 2, "created_at": null },
+            ],
+        });
+        assert_eq!(read_or_update(&value, &path, &config, true).unwrap(), None);
+
+        let snapshot = read(&path).unwrap();
+        assert_eq!(
+            snapshot,
+            json!({
+                "token": "{{any_string}}",
+                "items": [
+                    { "id": 1, "created_at": "{{not_null}}" },
+                    { "id": 2, "created_at": "{{any}}" },
+                ],
+            })
+        );
+
+        // The placeholders are read back as matchers.
+        let value = json!({
+            "token": "c6a2",
+            "items": [
+                { "id": 1, "created_at": "2025-06-14" },
+                { "id": 2, "created_at": "2025-06-14" },
+            ],
+        });
+        assert!(diff(&value, &snapshot, &config).is_empty());
+        let value = json!({
+            "token": 42,
+            "items": [{ "id": 1, "created_at": null }, { "id": 2, "created_at": null }],
+        });
+        assert_eq!(diff(&value, &snapshot, &config).len(), 2);
+    }
+
+    #[test]
+    fn test_matchers_are_kept() {
+        let config = Config::new(CompareMode::Strict);
+        let dir = SnapshotDir::new("matchers_are_kept");
+        let path = dir.path();
+        fs::write(
+            &path,
+            r#"{ "id": "{{any_number}}", "tags": ["{{any_string}}", "b"], "at": "{{any_string}}" }"#,
+        )
+        .unwrap();
+
+        let value = json!({ "id": 7, "tags": ["x", "c", "d"], "at": null, "name": "bob" });
+        assert_eq!(read_or_update(&value, &path, &config, true).unwrap(), None);
+        assert_eq!(
+            read(&path).unwrap(),
+            json!({
+                "id": "{{any_number}}",
+                "tags": ["{{any_string}}", "c", "d"],
+                "at": null,
+                "name": "bob",
+            })
+        );
+    }
+
+    #[test]
+    fn test_is_enabled() {
+        for value in ["1", "true", "yes", "TRUE", "on"] {
+            assert!(is_enabled(value), "{:?}", value);
+        }
+        for value in ["", "0", "false", "no", "False", "off"] {
+            assert!(!is_enabled(value), "{:?}", value);
+        }
+    }
+
+    #[test]
+    fn test_invalid_snapshot() {
+        let dir = SnapshotDir::new("invalid");
+        let path = dir.path();
+        fs::write(&path, "{ not json").unwrap();
+
+        let config = Config::new(CompareMode::Strict);
+        let err = read_or_update(&json!(1), &path, &config, false).unwrap_err();
+        assert_eq!(err.path(), path);
+        assert!(err.to_string().starts_with("Couldn't read snapshot"));
+    }
+}
@@ -0,0 +1,241 @@
+use crate::core_ext::Indent;
+use crate::diff::diff_counted;
+use crate::{color, render, to_values, CompareMode, Config, Difference, Error};
+use serde::Serialize;
+use std::{mem, thread};
+
+/// Collects the failures of several comparisons and panics once with all of them.
+///
+/// The assertion macros panic on the first failing comparison. `SoftAssertions` records every
+/// failure under a label instead, and reports them together when [`finish`](Self::finish) is
+/// called, or when it is dropped. That way a single test run shows every broken value across
+/// several responses.
+///
+/// ```should_panic
+/// use serde_json_assert::{CompareMode, Config, SoftAssertions};
+/// use serde_json::json;
+///
+/// let mut soft = SoftAssertions::new();
+/// soft.eq("user", &json!({ "id": 1, "name": "bob" }), &json!({ "id": 1, "name": "rob" }));
+/// soft.include("orders", &json!({ "count": 2, "items": [] }), &json!({ "count": 3 }));
+///
+/// let config = Config::new(CompareMode::Strict).consider_array_sorting(false);
+/// soft.matches("tags", &json!(["b", "a"]), &json!(["a", "b"]), &config);
+///
+/// // Panics with the failures of "user" and "orders".
+/// soft.finish();
+/// ```
+///
+/// This will panic with the error message:
+///
+/// ```text
+/// 2 of 3 soft assertions failed:
+///
+/// user:
+///     json atoms at path ".name" are not equal:
+///         lhs:
+///             "bob"
+///         rhs:
+///             "rob"
+///
+/// orders:
+///     json atoms at path ".count" are not equal:
+///         expected:
+///             3
+///         actual:
+///             2
+/// ```
+#[derive(Debug, Default)]
+pub struct SoftAssertions {
+    checks: usize,
+    failures: Vec<Failure>,
+}
+
+#[derive(Debug)]
+struct Failure {
+    label: String,
+    error: Error,
+    message: String,
+}
+
+impl SoftAssertions {
+    /// Create a collector without any failures.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Compare two JSON values according to a configuration, like
+    /// [`assert_json_matches`](macro.assert_json_matches.html), and record any failure under
+    /// `label`.
+    ///
+    /// Returns `true` if the values match.
+    pub fn matches<Lhs, Rhs>(
+        &mut self,
+        label: impl Into<String>,
+        lhs: &Lhs,
+        rhs: &Rhs,
+        config: &Config,
+    ) -> bool
+    where
+        Lhs: Serialize,
+        Rhs: Serialize,
+    {
+        self.checks += 1;
+        let (error, message) = match to_values(lhs, rhs) {
+            Ok((lhs, rhs)) => {
+                let (diffs, skipped) = diff_counted(&lhs, &rhs, config);
+                if diffs.is_empty() {
+                    return true;
+                }
+                let message = render(&lhs, &rhs, config, &diffs, skipped, color::enabled());
+                let differences = diffs.into_iter().map(Difference::from).collect();
+                (Error::Differences(differences), message)
+            }
+            Err(error) => {
+                let message = error.to_string();
+                (error, message)
+            }
+        };
+        self.failures.push(Failure {
+            label: label.into(),
+            error,
+            message,
+        });
+        false
+    }
+
+    /// Compare two JSON values for an exact match, like
+    /// [`assert_json_eq`](macro.assert_json_eq.html), and record any failure under `label`.
+    ///
+    /// Returns `true` if the values match.
+    pub fn eq<Lhs, Rhs>(&mut self, label: impl Into<String>, lhs: &Lhs, rhs: &Rhs) -> bool
+    where
+        Lhs: Serialize,
+        Rhs: Serialize,
+    {
+        self.matches(label, lhs, rhs, &Config::new(CompareMode::Strict))
+    }
+
+    /// Compare two JSON values for an inclusive match, like
+    /// [`assert_json_include`](macro.assert_json_include.html), and record any failure under
+    /// `label`.
+    ///
+    /// Returns `true` if `actual` includes `expected`.
+    pub fn include<Actual, Expected>(
+        &mut self,
+        label: impl Into<String>,
+        actual: &Actual,
+        expected: &Expected,
+    ) -> bool
+    where
+        Actual: Serialize,
+        Expected: Serialize,
+    {
+        self.matches(
+            label,
+            actual,
+            expected,
+            &Config::new(CompareMode::Inclusive),
+        )
+    }
+
+    /// Returns the label and error of every failed comparison so far, in the order they were
+    /// made.
+    pub fn failures(&self) -> impl Iterator<Item = (&str, &Error)> {
+        self.failures
+            .iter()
+            .map(|failure| (failure.label.as_str(), &failure.error))
+    }
+
+    /// Panics with a report of every failed comparison, if there are any.
+    #[track_caller]
+    pub fn finish(mut self) {
+        if let Some(report) = self.take_report() {
+            panic!("\n{}", report);
+        }
+    }
+
+    fn take_report(&mut self) -> Option<String> {
+        if self.failures.is_empty() {
+            return None;
+        }
+
+        let failures = mem::take(&mut self.failures);
+        let mut report = format!(
+            "{} of {} soft assertions failed:",
+            failures.len(),
+            self.checks
+        );
+        for failure in failures {
+            report.push_str(&format!(
+                "\n\n{}:\n{}",
+                failure.label,
+                failure.message.indent(4)
+            ));
+        }
+        Some(report)
+    }
+}
+
+/// Panics with the failures that weren't reported by [`SoftAssertions::finish`], unless the thread
+/// is already panicking.
+impl Drop for SoftAssertions {
+    fn drop(&mut self) {
+        if thread::panicking() {
+            return;
+        }
+        if let Some(report) = self.take_report() {
+            panic!("\n{}", report);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use std::panic;
+
+    #[test]
+    fn test_collects_failures() {
+        let mut soft = SoftAssertions::new();
+        assert!(soft.eq("same", &json!({ "a": 1 }), &json!({ "a": 1 })));
+        assert!(!soft.eq("first", &json!({ "a": 1 }), &json!({ "a": 2 })));
+        assert!(!soft.include("second", &json!([1]), &json!([1, 2])));
+
+        let failures = soft.failures().collect::<Vec<_>>();
+        assert_eq!(failures.len(), 2);
+        assert_eq!(failures[0].0, "first");
+        assert_eq!(failures[0].1.differences().len(), 1);
+        assert_eq!(failures[1].0, "second");
+
+        let report = soft.take_report().unwrap();
+        assert!(report.starts_with("2 of 3 soft assertions failed:\n\nfirst:\n    json atoms"));
+        assert!(report.contains("\n\nsecond:\n    json atom at path \"[1]\" is missing"));
+        assert_eq!(soft.failures().count(), 0);
+    }
+
+    #[test]
+    fn test_panics_once() {
+        let mut soft = SoftAssertions::new();
+        assert!(soft.eq("ok", &json!(1), &json!(1)));
+        soft.finish();
+
+        let result = panic::catch_unwind(|| {
+            let mut soft = SoftAssertions::new();
+            soft.eq("first", &json!(1), &json!(2));
+        });
+        let message = result.unwrap_err();
+        let message = message.downcast_ref::<String>().unwrap();
+        assert!(message.contains("1 of 1 soft assertions failed:"));
+
+        // A failure while already panicking is not reported twice.
+        let result = panic::catch_unwind(|| {
+            let mut soft = SoftAssertions::new();
+            soft.eq("first", &json!(1), &json!(2));
+            panic!("unrelated");
+        });
+        let message = result.unwrap_err();
+        assert_eq!(message.downcast_ref::<&str>(), Some(&"unrelated"));
+    }
+}
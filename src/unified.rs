@@ -0,0 +1,507 @@
+use crate::color::{Painter, Side};
+use crate::diff::{array_pairs, config_at, matches_at, KeyRef, PathRef};
+use crate::{CompareMode, Config};
+use serde_json::Value;
+use std::ops::Range;
+
+/// Render the differences between `lhs` and `rhs` as a unified diff of the two pretty-printed
+/// documents, keeping `context` unchanged lines around each change.
+///
+/// Values that match are printed once, as they appear in lhs. Values that don't match are
+/// descended into as long as both sides are objects or both are arrays, so only the atoms that
+/// actually differ are marked with `-` (lhs, actual) or `+` (rhs, expected). In
+/// [`CompareMode::Inclusive`] the data that actual may contain in addition to expected is
+/// printed as context.
+pub(crate) fn unified_diff(
+    lhs: &Value,
+    rhs: &Value,
+    config: &Config,
+    context: usize,
+    painter: Painter,
+) -> String {
+    let mut renderer = Renderer {
+        root: config,
+        lines: vec![],
+    };
+    let entry = Entry {
+        prefix: String::new(),
+        lhs: Some(lhs),
+        rhs: Some(rhs),
+        key: None,
+    };
+    renderer.entry(config, &PathRef::Root, &entry, 0, (false, false));
+
+    let (lhs_label, rhs_label) = match config.compare_mode {
+        CompareMode::Inclusive => ("actual", "expected"),
+        CompareMode::Strict => ("lhs", "rhs"),
+    };
+    let mut out = vec![
+        painter.diff_line(Some(Side::Actual), &format!("-- {}", lhs_label)),
+        painter.diff_line(Some(Side::Expected), &format!("++ {}", rhs_label)),
+    ];
+    for hunk in hunks(&renderer.lines, context) {
+        let path = renderer.lines[hunk.clone()]
+            .iter()
+            .find_map(|line| line.path.as_deref())
+            .unwrap_or_default();
+        out.push(format!("@@ {} @@", painter.path(path)));
+        for line in &renderer.lines[hunk] {
+            out.push(painter.diff_line(line.side, &line.text));
+        }
+    }
+    out.join("\n")
+}
+
+/// A line of the rendered diff.
+#[derive(Debug)]
+struct Line {
+    /// The side the line belongs to, or `None` for context lines.
+    side: Option<Side>,
+    text: String,
+    /// The path of the value this line belongs to, for lines that are part of a change.
+    path: Option<String>,
+}
+
+/// A member of an object or an array, with its value on either side.
+#[derive(Debug)]
+struct Entry<'a> {
+    /// Printed before the value, such as `"key": ` for object members.
+    prefix: String,
+    lhs: Option<&'a Value>,
+    rhs: Option<&'a Value>,
+    key: Option<KeyRef<'a>>,
+}
+
+/// How an entry is rendered.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum Rendering {
+    /// Printed once, unchanged, or descended into.
+    Shared,
+    /// Only printed on one side.
+    Only(Side),
+    /// Not printed at all.
+    Skipped,
+}
+
+#[derive(Debug)]
+struct Renderer<'a> {
+    root: &'a Config,
+    lines: Vec<Line>,
+}
+
+impl<'a> Renderer<'a> {
+    /// Render an entry whose lhs and rhs have already been paired. `commas` tells whether the
+    /// entry is followed by another one on the lhs and on the rhs side.
+    fn entry(
+        &mut self,
+        config: &'a Config,
+        path: &PathRef<'a>,
+        entry: &Entry<'a>,
+        level: usize,
+        commas: (bool, bool),
+    ) {
+        match (entry.lhs, entry.rhs) {
+            (Some(lhs), Some(rhs)) => self.pair(config, path, entry, (lhs, rhs), level, commas),
+            (Some(lhs), None) => match self.rendering(config, path, entry) {
+                Rendering::Only(side) => {
+                    self.value(Some(side), path, &entry.prefix, lhs, level, commas.0)
+                }
+                _ => self.value(None, path, &entry.prefix, lhs, level, commas.0 || commas.1),
+            },
+            (None, Some(rhs)) => self.value(
+                Some(Side::Expected),
+                path,
+                &entry.prefix,
+                rhs,
+                level,
+                commas.1,
+            ),
+            (None, None) => {}
+        }
+    }
+
+    fn pair(
+        &mut self,
+        config: &'a Config,
+        path: &PathRef<'a>,
+        entry: &Entry<'a>,
+        (lhs, rhs): (&'a Value, &'a Value),
+        level: usize,
+        commas: (bool, bool),
+    ) {
+        let matches =
+            self.is_ignored(config, path) || matches_at(lhs, rhs, self.root, config, path.clone());
+        if matches {
+            self.value(None, path, &entry.prefix, lhs, level, commas.0 || commas.1);
+            return;
+        }
+
+        let config = config_at(self.root, config, path);
+        let (open, close, entries) = match (lhs, rhs) {
+            (Value::Object(lhs), Value::Object(rhs)) => {
+                let entries = lhs
+                    .iter()
+                    .map(|(key, value)| (key, Some(value), rhs.get(key)))
+                    .chain(
+                        rhs.iter()
+                            .filter(|(key, _)| !lhs.contains_key(*key))
+                            .map(|(key, value)| (key, None, Some(value))),
+                    )
+                    .map(|(key, lhs, rhs)| Entry {
+                        prefix: format!("{}: ", Value::from(key.as_str())),
+                        lhs,
+                        rhs,
+                        key: Some(KeyRef::Field(key)),
+                    })
+                    .collect();
+                ('{', '}', entries)
+            }
+            (Value::Array(_), Value::Array(_)) => {
+                ('[', ']', self.array_entries(config, path, lhs, rhs))
+            }
+            _ => {
+                let (lhs_comma, rhs_comma) = commas;
+                self.value(
+                    Some(Side::Actual),
+                    path,
+                    &entry.prefix,
+                    lhs,
+                    level,
+                    lhs_comma,
+                );
+                self.value(
+                    Some(Side::Expected),
+                    path,
+                    &entry.prefix,
+                    rhs,
+                    level,
+                    rhs_comma,
+                );
+                return;
+            }
+        };
+
+        let renderings = entries
+            .iter()
+            .map(|entry| {
+                let path = entry.path(path);
+                self.rendering(config, &path, entry)
+            })
+            .collect::<Vec<_>>();
+
+        self.push(
+            None,
+            None,
+            format!("{}{}{}", indent(level), entry.prefix, open),
+        );
+        for (idx, entry) in entries.iter().enumerate() {
+            if renderings[idx] == Rendering::Skipped {
+                continue;
+            }
+            let later = &renderings[idx + 1..];
+            let commas = (
+                later.iter().any(|rendering| {
+                    matches!(rendering, Rendering::Shared | Rendering::Only(Side::Actual))
+                }),
+                later.iter().any(|rendering| {
+                    matches!(
+                        rendering,
+                        Rendering::Shared | Rendering::Only(Side::Expected)
+                    )
+                }),
+            );
+            self.entry(config, &entry.path(path), entry, level + 1, commas);
+        }
+        let comma = if commas.0 || commas.1 { "," } else { "" };
+        self.push(None, None, format!("{}{}{}", indent(level), close, comma));
+    }
+
+    /// Pair the elements of two arrays the same way they are compared.
+    fn array_entries(
+        &self,
+        config: &'a Config,
+        path: &PathRef<'a>,
+        lhs: &'a Value,
+        rhs: &'a Value,
+    ) -> Vec<Entry<'a>> {
+        array_pairs(lhs, rhs, self.root, config, path.clone())
+            .into_iter()
+            .map(|pair| Entry {
+                prefix: String::new(),
+                lhs: pair.lhs.map(|idx| &lhs[idx]),
+                rhs: pair.rhs.map(|idx| &rhs[idx]),
+                key: Some(pair.key),
+            })
+            .collect()
+    }
+
+    fn rendering(&self, config: &Config, path: &PathRef<'a>, entry: &Entry<'a>) -> Rendering {
+        match (entry.lhs, entry.rhs) {
+            (Some(_), Some(_)) => Rendering::Shared,
+            // Data that is only in actual is allowed in inclusive mode.
+            (Some(_), None)
+                if config.compare_mode == CompareMode::Inclusive
+                    || self.is_ignored(config, path) =>
+            {
+                Rendering::Shared
+            }
+            (Some(_), None) => Rendering::Only(Side::Actual),
+            (None, Some(_)) if self.is_ignored(config, path) => Rendering::Skipped,
+            (None, Some(_)) => Rendering::Only(Side::Expected),
+            (None, None) => Rendering::Skipped,
+        }
+    }
+
+    fn is_ignored(&self, config: &Config, path: &PathRef<'a>) -> bool {
+        config.is_ignored(path) || self.root.is_ignored(path)
+    }
+
+    /// Pretty print `value` on the given side.
+    fn value(
+        &mut self,
+        side: Option<Side>,
+        path: &PathRef<'a>,
+        prefix: &str,
+        value: &Value,
+        level: usize,
+        comma: bool,
+    ) {
+        let text = serde_json::to_string_pretty(value).unwrap();
+        let path = side.map(|_| path.to_string());
+        let mut lines = text.lines().peekable();
+        let mut first = true;
+        while let Some(line) = lines.next() {
+            let prefix = if first { prefix } else { "" };
+            let comma = if comma && lines.peek().is_none() {
+                ","
+            } else {
+                ""
+            };
+            self.push(
+                side,
+                path.clone(),
+                format!("{}{}{}{}", indent(level), prefix, line, comma),
+            );
+            first = false;
+        }
+    }
+
+    fn push(&mut self, side: Option<Side>, path: Option<String>, text: String) {
+        self.lines.push(Line { side, text, path });
+    }
+}
+
+impl<'a> Entry<'a> {
+    fn path(&self, parent: &PathRef<'a>) -> PathRef<'a> {
+        match &self.key {
+            Some(key) => parent.append(*key),
+            None => parent.clone(),
+        }
+    }
+}
+
+fn indent(level: usize) -> String {
+    "  ".repeat(level)
+}
+
+/// Returns the ranges of lines to print, so that every changed line is surrounded by up to
+/// `context` unchanged lines.
+fn hunks(lines: &[Line], context: usize) -> Vec<Range<usize>> {
+    let mut hunks: Vec<Range<usize>> = vec![];
+    for (idx, line) in lines.iter().enumerate() {
+        if line.side.is_none() {
+            continue;
+        }
+        let start = idx.saturating_sub(context);
+        let end = (idx + context + 1).min(lines.len());
+        match hunks.last_mut() {
+            Some(last) if last.end >= start => last.end = end,
+            _ => hunks.push(start..end),
+        }
+    }
+    hunks
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Matcher;
+    use serde_json::json;
+
+    fn render(lhs: Value, rhs: Value, config: &Config) -> String {
+        unified_diff(&lhs, &rhs, config, 2, Painter { color: false })
+    }
+
+    #[test]
+    fn test_objects() {
+        let config = Config::new(CompareMode::Strict);
+        let diff = render(
+            json!({ "a": 1, "b": { "c": "x", "d": "y" }, "e": 2 }),
+            json!({ "a": 1, "b": { "c": "x", "d": "z" }, "f": 3 }),
+            &config,
+        );
+        assert_eq!(
+            diff,
+            r#"--- lhs
++++ rhs
+@@ .b.d @@
+   "b": {
+     "c": "x",
+-    "d": "y"
++    "d": "z"
+   },
+-  "e": 2
++  "f": 3
+ }"#
+        );
+    }
+
+    #[test]
+    fn test_inclusive_shows_extra_actual_data_as_context() {
+        let config = Config::new(CompareMode::Inclusive);
+        let diff = render(
+            json!({ "a": 1, "b": 2, "c": 3 }),
+            json!({ "b": 3, "d": 4 }),
+            &config,
+        );
+        assert_eq!(
+            diff,
+            r#"--- actual
++++ expected
+@@ .b @@
+ {
+   "a": 1,
+-  "b": 2,
++  "b": 3,
+   "c": 3,
++  "d": 4
+ }"#
+        );
+    }
+
+    #[test]
+    fn test_arrays() {
+        let config = Config::new(CompareMode::Strict);
+        let diff = render(json!([1, 2, 3]), json!([1, 5]), &config);
+        assert_eq!(
+            diff,
+            r#"--- lhs
++++ rhs
+@@ [1] @@
+ [
+   1,
+-  2,
++  5
+-  3
+ ]"#
+        );
+
+        // Aligned arrays only mark the inserted element.
+        let config = Config::new(CompareMode::Strict).align_arrays(true);
+        let diff = render(json!([1, 2, 3]), json!([1, 5, 2, 3]), &config);
+        assert_eq!(
+            diff,
+            r#"--- lhs
++++ rhs
+@@ [1] @@
+ [
+   1,
++  5,
+   2,
+   3"#
+        );
+    }
+
+    #[test]
+    fn test_hunks() {
+        let config = Config::new(CompareMode::Strict);
+        let lhs = json!((0..20).collect::<Vec<_>>());
+        let mut rhs = lhs.clone();
+        rhs[2] = json!(-2);
+        rhs[15] = json!(-15);
+        let diff = render(lhs, rhs, &config);
+        assert_eq!(
+            diff,
+            r#"--- lhs
++++ rhs
+@@ [2] @@
+   0,
+   1,
+-  2,
++  -2,
+   3,
+   4,
+@@ [15] @@
+   13,
+   14,
+-  15,
++  -15,
+   16,
+   17,"#
+        );
+    }
+
+    #[test]
+    fn test_ignored_paths_are_context() {
+        let config = Config::new(CompareMode::Strict)
+            .ignore_path(".id")
+            .ignore_path(".extra");
+        let diff = render(
+            json!({ "id": 1, "name": "a" }),
+            json!({ "id": 2, "name": "b", "extra": true }),
+            &config,
+        );
+        assert_eq!(
+            diff,
+            r#"--- lhs
++++ rhs
+@@ .name @@
+ {
+   "id": 1,
+-  "name": "a"
++  "name": "b"
+ }"#
+        );
+    }
+
+    #[test]
+    fn test_arrays_are_paired_like_their_differences() {
+        // The matcher accepts "x", so only the unmatched `3` is marked.
+        let config = Config::new(CompareMode::Inclusive).consider_array_sorting(false);
+        let diff = render(
+            json!([1, "x", 2]),
+            json!([Matcher::AnyString, 1, 3]),
+            &config,
+        );
+        assert_eq!(
+            diff,
+            r#"--- actual
++++ expected
+@@ [2] @@
+   1,
+   "x",
+-  2
++  3
+ ]"#
+        );
+
+        let config = Config::new(CompareMode::Strict).match_arrays_by_key(["id"]);
+        let diff = render(
+            json!([{ "id": 1, "v": "a" }, { "id": 2, "v": "b" }]),
+            json!([{ "id": 2, "v": "c" }, { "id": 1, "v": "a" }]),
+            &config,
+        );
+        assert_eq!(
+            diff,
+            r#"--- lhs
++++ rhs
+@@ [1:id=2].v @@
+   {
+     "id": 2,
+-    "v": "b"
++    "v": "c"
+   }
+ ]"#
+        );
+    }
+}
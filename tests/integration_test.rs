@@ -2,7 +2,10 @@ use serde::Serialize;
 use serde_json::json;
 use serde_json_assert::{
     assert_json_contains, assert_json_eq, assert_json_include, assert_json_matches,
-    assert_json_matches_no_panic, CompareMode, Config, FloatCompareMode, NumericMode,
+    assert_json_matches_no_panic, assert_json_ne, assert_json_not_include, assert_json_snapshot,
+    debug_assert_json_contains, debug_assert_json_eq, debug_assert_json_include,
+    debug_assert_json_matches, json_matches, try_assert_json_matches, CompareMode, Config,
+    FloatCompareMode, Matcher, NumericMode, PathPattern, SoftAssertions,
 };
 
 #[test]
@@ -449,3 +452,203 @@ fn assert_json_matches_can_fail_with_message() {
     let msg = error.downcast_ref::<String>().unwrap();
     assert!(msg.contains("The 'matches' assert failed because of 'reasons'"));
 }
+
+#[test]
+fn can_pass_with_matchers() {
+    assert_json_include!(
+        actual: json!({
+            "id": 42,
+            "name": "bob",
+            "tags": ["admin"],
+            "created_at": "2025-06-13T12:00:00Z",
+        }),
+        expected: json!({
+            "id": Matcher::AnyNumber,
+            "name": "bob",
+            "tags": Matcher::AnyArray,
+            "created_at": Matcher::NotNull,
+        })
+    );
+}
+
+#[test]
+fn matcher_failures_are_reported() {
+    let config = Config::new(CompareMode::Inclusive);
+    let error = try_assert_json_matches(
+        &json!({ "id": "42", "name": "bob" }),
+        &json!({ "id": Matcher::AnyNumber, "name": Matcher::AnyString }),
+        &config,
+    )
+    .unwrap_err();
+    let differences = error.differences();
+
+    assert_eq!(differences.len(), 1);
+    assert_eq!(differences[0].matcher(), Some(Matcher::AnyNumber));
+    assert_eq!(differences[0].actual(), &Some(json!("42")));
+}
+
+#[test]
+fn regex_matcher_failures_expose_the_pattern() {
+    let config = Config::new(CompareMode::Strict);
+    assert_json_matches!(
+        json!({ "id": "8f14e45f-ceea-467f-a0e6-5e1b1e1b1e1b", "version": "1.2.3" }),
+        json!({
+            "id": Matcher::regex("^[0-9a-f]{8}(-[0-9a-f]{4}){3}-[0-9a-f]{12}$"),
+            "version": Matcher::regex(r"^\d+\.\d+\.\d+$"),
+        }),
+        &config
+    );
+
+    let error = try_assert_json_matches(
+        &json!({ "version": "1.2" }),
+        &json!({ "version": Matcher::regex(r"^\d+\.\d+\.\d+$") }),
+        &config,
+    )
+    .unwrap_err();
+    let differences = error.differences();
+
+    assert_eq!(differences.len(), 1);
+    assert_eq!(
+        differences[0].matcher(),
+        Some(Matcher::regex(r"^\d+\.\d+\.\d+$"))
+    );
+}
+
+#[test]
+fn ignore_paths_matching_patterns() {
+    let pattern: PathPattern = ".**.created_at".parse().unwrap();
+    let config = Config::new(CompareMode::Strict)
+        .ignore_path(".meta.*")
+        .ignore_path(pattern);
+
+    assert_json_matches!(
+        json!({
+            "meta": { "request_id": "a", "took_ms": 12 },
+            "items": [{ "id": 1, "created_at": 1 }, { "id": 2, "created_at": 2 }],
+        }),
+        json!({
+            "meta": { "request_id": "b" },
+            "items": [{ "id": 1 }, { "id": 2, "created_at": 3 }],
+        }),
+        &config
+    );
+}
+
+#[test]
+fn serialization_failures_are_returned() {
+    // JSON objects can only have string keys.
+    let map = std::collections::HashMap::from([((1, 2), 3)]);
+    let config = Config::new(CompareMode::Strict);
+
+    let error = try_assert_json_matches(&json!({}), &map, &config).unwrap_err();
+    assert!(matches!(error, serde_json_assert::Error::SerializeRhs(_)));
+    assert!(error.differences().is_empty());
+
+    let message = assert_json_matches_no_panic(&map, &json!({}), &config).unwrap_err();
+    assert!(message.starts_with("Couldn't convert left hand side value to JSON."));
+}
+
+#[test]
+fn can_pass_with_snapshot() {
+    #[derive(Serialize)]
+    struct User {
+        id: u32,
+        name: &'static str,
+        roles: Vec<&'static str>,
+    }
+
+    let user = User {
+        id: 42,
+        name: "bob",
+        roles: vec!["admin", "user"],
+    };
+    assert_json_snapshot!(user, "tests/snapshots/user.json");
+
+    let config = Config::new(CompareMode::Strict).consider_array_sorting(false);
+    assert_json_snapshot!(
+        json!({ "id": 7, "name": "bob", "roles": ["user", "admin"] }),
+        "tests/snapshots/user.json",
+        &config,
+        "Roles may be in any order"
+    );
+}
+
+#[test]
+#[should_panic(expected = "2 of 3 soft assertions failed:")]
+fn soft_assertions_report_every_failure() {
+    let mut soft = SoftAssertions::new();
+    soft.eq("user", &json!({ "id": 1 }), &json!({ "id": 2 }));
+    soft.include("orders", &json!({ "count": 2 }), &json!({ "count": 2 }));
+    soft.matches(
+        "tags",
+        &json!(["b", "a"]),
+        &json!(["a", "c"]),
+        &Config::new(CompareMode::Strict).consider_array_sorting(false),
+    );
+    soft.finish();
+}
+
+#[test]
+fn can_pass_with_negative_assertions() {
+    assert_json_ne!(json!({ "a": 1 }), json!({ "a": 1, "b": 2 }));
+    assert_json_not_include!(
+        actual: json!({ "user": { "name": "bob" } }),
+        expected: json!({ "user": { "token": Matcher::Any } }),
+    );
+}
+
+#[test]
+#[should_panic(expected = "json values are equal")]
+fn assert_json_ne_can_fail() {
+    assert_json_ne!(json!({ "a": [1, 2] }), json!({ "a": [1, 2] }));
+}
+
+#[test]
+#[should_panic(expected = "The token was not removed")]
+fn assert_json_not_include_can_fail_with_message() {
+    assert_json_not_include!(
+        actual: json!({ "user": { "name": "bob", "token": "b5f1" } }),
+        expected: json!({ "user": { "token": Matcher::AnyString } }),
+        "The token was not removed"
+    );
+}
+
+#[test]
+fn can_pass_with_debug_assertions() {
+    let config = Config::new(CompareMode::Inclusive);
+    debug_assert_json_eq!(json!({ "a": 1 }), json!({ "a": 1 }));
+    debug_assert_json_include!(expected: json!({ "a": 1 }), actual: json!({ "a": 1, "b": 2 }));
+    debug_assert_json_contains!(container: json!([1, 2]), contained: json!([2]), "message");
+    debug_assert_json_matches!(json!({ "a": 1, "b": 2 }), json!({ "a": 1 }), &config);
+}
+
+#[test]
+#[cfg(debug_assertions)]
+#[should_panic(expected = "Debug assertions are enabled")]
+fn debug_assertions_can_fail_with_message() {
+    debug_assert_json_eq!(
+        json!({ "a": 1 }),
+        json!({ "a": 2 }),
+        "Debug assertions are {}",
+        "enabled"
+    );
+}
+
+#[test]
+fn json_matches_can_filter_values() {
+    let config = Config::new(CompareMode::Inclusive).consider_array_sorting(false);
+    let mut orders = vec![
+        json!({ "id": 1, "tags": ["new", "paid"] }),
+        json!({ "id": 2, "tags": ["new"] }),
+        json!({ "id": 3, "tags": ["paid", "shipped"] }),
+    ];
+    orders.retain(|order| json_matches(order, &json!({ "tags": ["paid"] }), &config));
+
+    assert_json_eq!(
+        orders,
+        json!([
+            { "id": 1, "tags": ["new", "paid"] },
+            { "id": 3, "tags": ["paid", "shipped"] },
+        ])
+    );
+}